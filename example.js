@@ -4,11 +4,13 @@ const wasmReady = init();
 
 wasmReady.then(() => {
     // Hints strings needs to have lines separated by semi-colon and "blocks" separated by commas
+    // hintsX are the row hints (top to bottom), hintsY the column hints (left to right)
     let hintsX = "2,2;4;1;2,1;1" 
     let hintsY = "1,2;2,1;1,1;2,1;2"    
-    let dim = "5x5"
+    let dim = "5x5" // <columns>x<rows>
     
     const solution = solve(hintsX, hintsY, dim)
+    // Or, with the argument order spelled out: solve_rows_cols(hintsX, hintsY, dim)
     
     // `solution` is the solved board as a string, e.g:
     //  
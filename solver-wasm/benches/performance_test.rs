@@ -1,13 +1,14 @@
 use itertools::{max, min};
 use serde::{Deserialize, Serialize};
-use serde_json;
 use statistical::{mean, standard_deviation};
 use std::fs::File;
+use std::hint::black_box;
 use std::io::{BufRead, BufReader, Write};
 use std::process::Command;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use solver_wasm::solve;
+use solver_wasm::formats::art;
+use solver_wasm::model::{Axis, Board, Line, Puzzle, SolveConfig, SolveStats};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 struct TestData {
@@ -19,17 +20,15 @@ struct TestData {
 }
 
 impl TestData {
-    fn hints_to_str(hint: &Vec<Vec<i32>>) -> String {
+    fn hints_to_clues(hint: &[Vec<i32>]) -> Vec<Vec<usize>> {
         hint
             .iter()
-            .map(|line| {
-                line.iter()
-                    .map(|&v| v.to_string())
-                    .collect::<Vec<_>>()
-                    .join(",") 
-                })
-            .collect::<Vec<_>>()
-            .join(";")
+            .map(|line| line.iter().map(|&v| v as usize).collect())
+            .collect()
+    }
+
+    fn puzzle(&self) -> Puzzle {
+        Puzzle::from_clues(&TestData::hints_to_clues(&self.hints_x), &TestData::hints_to_clues(&self.hints_y))
     }
 }
 
@@ -42,7 +41,7 @@ fn read_test_data(path: &str) -> Vec<TestData> {
     let file = File::open(path).expect("Failed to open file");
     let reader = BufReader::new(file);
 
-    let testData = reader
+    reader
         .lines()
         .map(|line| line.expect("Failed to read line"))
         .filter(|line| !line.trim().is_empty())
@@ -50,13 +49,12 @@ fn read_test_data(path: &str) -> Vec<TestData> {
             let parsed: JsonLine = serde_json::from_str(&line).expect("Failed to parse line");
             parsed.data
         })
-        .collect::<Vec<TestData>>();
-    testData
+        .collect::<Vec<TestData>>()
 }
 
 fn get_git_commit_hash() -> String {
     let output = Command::new("git")
-        .args(&["rev-parse", "HEAD"])
+        .args(["rev-parse", "HEAD"])
         .output()
         .expect("Failed to get git commit hash");
     
@@ -70,23 +68,36 @@ fn get_git_commit_hash() -> String {
 struct TestResults {
     dim: String,
     num_samples: usize,
-    max_us: u32,
-    min_us: u32,
+    max_us: u128,
+    min_us: u128,
     mean_us: f64,
-    std_us: f64
+    std_us: f64,
+    mean_setup_us: f64,
+    mean_solve_us: f64,
+    mean_passes: f64,
+    mean_candidates: f64
 }
 
 impl TestResults {
-    fn from_times(dim: &str, num_samples: usize, times: &Vec<u128>) -> TestResults {
+    fn from_runs(dim: &str, num_samples: usize, setup_times: &[u128], solve_times: &[u128], stats: &[SolveStats]) -> TestResults {
+        let times = setup_times.iter().zip(solve_times).map(|(setup, solve)| setup + solve).collect::<Vec<_>>();
         let times_f = times.iter().map(|&v| v as f64).collect::<Vec<_>>();
+        let setup_f = setup_times.iter().map(|&v| v as f64).collect::<Vec<_>>();
+        let solve_f = solve_times.iter().map(|&v| v as f64).collect::<Vec<_>>();
+        let passes = stats.iter().map(|s| s.passes as f64).collect::<Vec<_>>();
+        let candidates = stats.iter().map(|s| s.candidates_generated as f64).collect::<Vec<_>>();
 
         TestResults {
             dim: dim.to_string(),
-            num_samples: num_samples,
-            max_us: *max(times).unwrap() as u32,
-            min_us: *min(times).unwrap() as u32,
+            num_samples,
+            max_us: *max(&times).unwrap(),
+            min_us: *min(&times).unwrap(),
             mean_us: mean(&times_f),
-            std_us: standard_deviation(&times_f, None)
+            std_us: standard_deviation(&times_f, None),
+            mean_setup_us: mean(&setup_f),
+            mean_solve_us: mean(&solve_f),
+            mean_passes: mean(&passes),
+            mean_candidates: mean(&candidates)
         }
     }
 
@@ -97,6 +108,7 @@ impl TestResults {
             .expect("Time should go forward")
             .as_secs();
         let filename = format!("benches/results/bench_results_{}.txt", seconds_since_epoch);
+        std::fs::create_dir_all("benches/results").expect("Failed to create benchmark results directory");
         
         let mut file = File::options()
             .create(true)
@@ -114,12 +126,61 @@ impl TestResults {
     }
 }
 
-fn print_time<T>(time_us: T) -> String
-where
-    T: Into<f64> + Copy,
-{
-    let time = time_us.into();
-    
+/// Reads back the mean of every result in a file written by `TestResults::save`, as
+/// `(dimensions, mean_us)`.
+fn read_baseline(contents: &str) -> Vec<(String, f64)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.strip_prefix("Result: TestResults (")?;
+            let (dim, rest) = line.split_once(" and ")?;
+            let (_, rest) = rest.split_once("Mean: ")?;
+            let (mean, _) = rest.split_once(',')?;
+            Some((dim.to_string(), parse_time(mean)?))
+        })
+        .collect()
+}
+
+/// The most recently saved results file, if any.
+fn latest_baseline() -> Option<String> {
+    let latest = std::fs::read_dir("benches/results")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("bench_results_")))
+        // The file names end in the seconds since the epoch, which all have the same length
+        .max()?;
+    std::fs::read_to_string(latest).ok()
+}
+
+/// Dimensions whose mean time grew by more than `max_regression_percent` over the baseline.
+/// Dimensions missing from the baseline aren't compared.
+fn regressions(baseline: &[(String, f64)], results: &[TestResults], max_regression_percent: f64) -> Vec<String> {
+    results
+        .iter()
+        .filter(|res| {
+            baseline
+                .iter()
+                .find(|(dim, _)| *dim == res.dim)
+                .is_some_and(|(_, mean_us)| res.mean_us > mean_us * (1.0 + max_regression_percent / 100.0))
+        })
+        .map(|res| res.dim.clone())
+        .collect()
+}
+
+/// Inverse of `print_time`.
+fn parse_time(time: &str) -> Option<f64> {
+    let (value, unit) = time.trim().split_once(' ')?;
+    let value = value.parse::<f64>().ok()?;
+    match unit {
+        "s" => Some(value * 1_000_000.0),
+        "ms" => Some(value * 1_000.0),
+        "μs" => Some(value),
+        _ => None
+    }
+}
+
+fn print_time(time: f64) -> String {
     if time >= 1_000_000.0 {
         format!("{:.3} s", time / 1_000_000.0)
     } else if time >= 1_000.0 {
@@ -131,32 +192,143 @@ where
 
 impl std::fmt::Display for TestResults {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let min = print_time(self.min_us);
-        let max = print_time(self.max_us);
+        let min = print_time(self.min_us as f64);
+        let max = print_time(self.max_us as f64);
         let mean = print_time(self.mean_us);
         let std = print_time(self.std_us);
+        let setup = print_time(self.mean_setup_us);
+        let solve = print_time(self.mean_solve_us);
         
-        write!(f, "TestResults ({} and {} samples). Mean: {}, Min: {}, Max: {}, Std: {}", self.dim, self.num_samples, mean, min, max, std)
+        write!(f, "TestResults ({} and {} samples). Mean: {}, Min: {}, Max: {}, Std: {}, Mean setup: {}, Mean solve: {}, Mean passes: {:.1}, Mean candidates: {:.0}", self.dim, self.num_samples, mean, min, max, std, setup, solve, self.mean_passes, self.mean_candidates)
     }
 }
 
 // TODO: Compare solution from test data to proposed solution from solver
 fn run_performance_test(dimensions: &str, test_data: &Vec<TestData>) -> TestResults {
-    let mut execution_time = Vec::new();
+    let mut setup_time = Vec::new();
+    let mut solve_time = Vec::new();
+    let mut stats = Vec::new();
     for data in test_data {
-        let constraints_x = TestData::hints_to_str(&data.hints_x);
-        let constraints_y = TestData::hints_to_str(&data.hints_y);
+        let puzzle = data.puzzle();
+
+        // Board::new generates the candidate lines, time it separately from the propagation
+        let now = Instant::now();
+        let mut board = puzzle.board();
+        setup_time.push(now.elapsed().as_micros());
+
+        let now = Instant::now();
+        board.solve();
+        solve_time.push(now.elapsed().as_micros());
+        stats.push(board.stats().clone());
+    }
+
+    TestResults::from_runs(dimensions, test_data.len(), &setup_time, &solve_time, &stats)
+}
+
+fn run_wide_line_test(width: usize) {
+    // Evenly spaced short blocks leave every row with a huge number of arrangements
+    let art = (0..4)
+        .map(|row| {
+            (0..width)
+                .map(|col| if (col + row) % 8 < 2 { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (constraints, dimensions) = art::parse(&art);
+
+    for streaming in [false, true] {
+        let config = SolveConfig {
+            streaming,
+            ..SolveConfig::default()
+        };
 
         let now = Instant::now();
-        solve(&constraints_x, &constraints_y, dimensions);
+        let mut board = Board::with_config(constraints.clone(), dimensions, config);
+        board.solve();
         let elapsed = now.elapsed();
-        execution_time.push(elapsed.as_micros());
+
+        println!("Wide lines (4x{}, streaming: {}). Time: {}", width, streaming, print_time(elapsed.as_micros() as f64));
     }
+}
+
+/// Sums every line's initial candidates once with the lines kept as bitmasks, as `Line::new`
+/// does for lines of up to 128 cells, and once with them kept as `Vec`s.
+fn run_line_backend_test(dimensions: &str, test_data: &[TestData]) {
+    let bitmask_lines = test_data
+        .iter()
+        .flat_map(|data| {
+            let board = data.puzzle().board();
+            let num_rows = board.clues(Axis::Row).len();
+            let num_cols = board.clues(Axis::Col).len();
+            (0..num_rows)
+                .map(|idx| board.candidates(Axis::Row, idx).to_vec())
+                .chain((0..num_cols).map(|idx| board.candidates(Axis::Col, idx).to_vec()))
+                .collect::<Vec<_>>()
+        })
+        .filter(|candidates| !candidates.is_empty())
+        .collect::<Vec<Vec<Line>>>();
+    let vec_lines = bitmask_lines
+        .iter()
+        .map(|candidates| candidates.iter().map(|line| Line::with_cells_backend(line.cells())).collect())
+        .collect::<Vec<Vec<Line>>>();
+    let num_candidates = bitmask_lines.iter().map(Vec::len).sum::<usize>();
 
-    TestResults::from_times(dimensions, test_data.len(), &execution_time)
+    for (backend, lines) in [("bitmask", &bitmask_lines), ("vec", &vec_lines)] {
+        let now = Instant::now();
+        for candidates in lines {
+            black_box(candidates[1..].iter().fold(candidates[0].clone(), |sum, line| &sum & line));
+        }
+        let elapsed = now.elapsed();
+
+        println!("Line backend ({}, {}, {} candidates). Time: {}", dimensions, backend, num_candidates, print_time(elapsed.as_micros() as f64));
+    }
+}
+
+/// Times are kept in whole microseconds as `u128`, so solves longer than `u32::MAX` μs must
+/// still come out right. Checked before every run since the bench has no test harness.
+fn check_long_times() {
+    let long_us = u32::MAX as u128 + 1_000_000;
+    let stats = vec![SolveStats::default(); 2];
+    let res = TestResults::from_runs("check", 2, &[0, 0], &[long_us, 10], &stats);
+
+    assert_eq!(res.max_us, long_us);
+    assert_eq!(res.min_us, 10);
+    assert_eq!(print_time(res.max_us as f64), "4295.967 s");
+}
+
+/// Runs the regression comparison on a made up baseline. Like `check_long_times` this runs
+/// before every bench.
+fn check_regression_guard() {
+    let baseline = read_baseline(
+        "Commit: abc\n---\n\
+        Result: TestResults (5x5 and 2 samples). Mean: 20.000 μs, Min: 10.000 μs\n\
+        Result: TestResults (10x10 and 2 samples). Mean: 1.000 ms, Min: 900.000 μs\n"
+    );
+    assert_eq!(baseline, vec![("5x5".to_string(), 20.0), ("10x10".to_string(), 1000.0)]);
+
+    let stats = vec![SolveStats::default(); 2];
+    let results = vec![
+        // 10% slower and 50% slower
+        TestResults::from_runs("5x5", 2, &[0, 0], &[22, 22], &stats),
+        TestResults::from_runs("10x10", 2, &[0, 0], &[1500, 1500], &stats),
+        TestResults::from_runs("15x15", 2, &[0, 0], &[5000, 5000], &stats)
+    ];
+    assert_eq!(regressions(&baseline, &results, 20.0), vec!["10x10".to_string()]);
+    assert_eq!(regressions(&baseline, &results, 5.0), vec!["5x5".to_string(), "10x10".to_string()]);
 }
 
 fn main() {
+    check_long_times();
+    check_regression_guard();
+
+    // With BENCH_MAX_REGRESSION=<percent>, fail if any mean is that much slower than in the
+    // last saved results. Read before this run's results are saved.
+    let max_regression = std::env::var("BENCH_MAX_REGRESSION")
+        .ok()
+        .map(|percent| percent.parse::<f64>().expect("BENCH_MAX_REGRESSION should be a percentage"));
+    let baseline = max_regression.and_then(|_| latest_baseline()).map(|contents| read_baseline(&contents));
+
     // TODO: There is an issue with non-square boards.
     let dimensions = vec![
         ("5x5", 0), 
@@ -180,7 +352,22 @@ fn main() {
         results.push(res);
     }
 
+    run_wide_line_test(40);
+    run_line_backend_test("25x25", &read_test_data("data/25x25.json")[..20]);
+
     if std::env::var("SAVE_BENCH").is_ok() {
         TestResults::save(&results);
     }
+
+    if let Some(max_regression) = max_regression {
+        let Some(baseline) = baseline else {
+            println!("No saved results to compare against");
+            return
+        };
+        let regressed = regressions(&baseline, &results, max_regression);
+        if !regressed.is_empty() {
+            println!("Regressed by more than {}%: {}", max_regression, regressed.join(", "));
+            std::process::exit(1);
+        }
+    }
 }
\ No newline at end of file
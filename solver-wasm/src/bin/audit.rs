@@ -0,0 +1,52 @@
+//! Solves every puzzle in the `data/*.json` corpus and checks the result against the stored
+//! solution, printing the number of mismatches per dimension.
+//!
+//! Run with `cargo run --release --features corpus --bin audit [data dir]`. Exits with status `0`
+//! when every puzzle solves to its stored solution and `1` if any puzzle is left unsolved or
+//! solves to a different grid.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use solver_wasm::corpus::read_corpus;
+use solver_wasm::model::Board;
+
+fn main() -> ExitCode {
+    let data_dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/data")));
+
+    let mut paths = std::fs::read_dir(&data_dir)
+        .expect("Failed to read data directory")
+        .map(|entry| entry.expect("Failed to read directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    let mut total_mismatches = 0;
+    for path in paths {
+        let puzzles = read_corpus(&path).expect("Failed to read corpus file");
+        let dim = path.file_stem().unwrap().to_string_lossy();
+
+        let mut mismatches = Vec::new();
+        for (idx, puzzle) in puzzles.iter().enumerate() {
+            let mut board = Board::new(puzzle.constraints(), puzzle.dimensions());
+            board.solve();
+
+            if board.grid() != puzzle.solution_grid() {
+                mismatches.push(idx);
+            }
+        }
+
+        println!("{}: {}/{} solved, mismatches: {:?}", dim, puzzles.len() - mismatches.len(), puzzles.len(), mismatches);
+        total_mismatches += mismatches.len();
+    }
+
+    if total_mismatches > 0 {
+        println!("{} puzzles did not solve to their stored solution", total_mismatches);
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::model::{CellState, Constraint, Constraints, Dimensions};
+
+/// One puzzle from the JSONL files in `data/`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorpusPuzzle {
+    /// One `Vec` per row, `2` for full and `1` for empty cells
+    pub solution: Vec<Vec<u8>>,
+    /// The row clues
+    #[serde(rename = "hintsX")]
+    pub hints_x: Vec<Vec<usize>>,
+    /// The column clues
+    #[serde(rename = "hintsY")]
+    pub hints_y: Vec<Vec<usize>>
+}
+
+impl CorpusPuzzle {
+    pub fn constraints(&self) -> Constraints {
+        let to_constraints = |hints: &[Vec<usize>]| {
+            hints
+                .iter()
+                .map(|values| Constraint::new(values.clone()))
+                .collect::<Vec<Constraint>>()
+        };
+
+        Constraints::new(to_constraints(&self.hints_y), to_constraints(&self.hints_x))
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions::new(self.hints_x.len(), self.hints_y.len())
+    }
+
+    pub fn solution_grid(&self) -> Vec<Vec<CellState>> {
+        self.solution
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&value| if value == 2 { CellState::Full } else { CellState::Empty })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct CorpusLine {
+    data: CorpusPuzzle
+}
+
+/// Reads a JSONL corpus file with one `{"data": {...}}` puzzle per line. Blank lines are skipped.
+pub fn read_corpus(path: impl AsRef<Path>) -> io::Result<Vec<CorpusPuzzle>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut puzzles = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue
+        }
+
+        let parsed = serde_json::from_str::<CorpusLine>(&line)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        puzzles.push(parsed.data);
+    }
+    Ok(puzzles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_corpus() {
+        let puzzles = read_corpus(concat!(env!("CARGO_MANIFEST_DIR"), "/data/5x5.json")).unwrap();
+        let puzzle = &puzzles[0];
+
+        assert_eq!(puzzle.dimensions(), Dimensions::new(5, 5));
+        assert_eq!(puzzle.constraints().duplicate_rows(), vec![vec![3, 4]]);
+        assert_eq!(puzzle.solution_grid()[0][..2], [CellState::Full, CellState::Empty]);
+    }
+}
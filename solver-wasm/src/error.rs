@@ -0,0 +1,124 @@
+use std::fmt;
+
+use crate::model::Axis;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolverError {
+    /// The line still has more than one possible arrangement
+    LineNotForced { index: usize, axis: Axis },
+    /// A grid doesn't have the board's number of rows and columns
+    GridSizeMismatch { expected: (usize, usize), actual: (usize, usize) },
+    /// The line's cells don't match its clue
+    LineMismatch { index: usize, axis: Axis },
+    /// An output buffer can't hold one entry per cell
+    BufferTooSmall { required: usize, actual: usize },
+    /// The row clues and the column clues don't add up to the same number of full cells
+    ClueTotalMismatch { row_total: usize, col_total: usize },
+    /// The line's clue doesn't fit in the line at all
+    Infeasible { index: usize, axis: Axis },
+    /// Every clue fits on its own, but no grid satisfies all of them
+    Contradiction,
+    /// The clues have more than one solution
+    Ambiguous,
+    /// No arrangement of the line's clue agrees with the cells already on the grid
+    NoArrangement { index: usize, axis: Axis },
+    /// A dimension string isn't of the form `<columns>x<rows>`
+    InvalidDimensions(String),
+    /// A side of the board is larger than the accepted maximum
+    DimensionTooLarge { size: usize, max: usize },
+    /// A number doesn't encode any `CellState`
+    InvalidCellValue(i8),
+    /// A list of block positions doesn't have one start per block length
+    PositionCountMismatch { starts: usize, lengths: usize },
+    /// The block overlaps or touches the block before it
+    BlockOverlap { block: usize },
+    /// The block runs past the end of the line
+    BlockOutOfRange { block: usize, length: usize },
+    /// A string isn't a grid as written by `Board::to_rle`
+    InvalidRle(String),
+    /// A combined puzzle string isn't of the form `<rows>|<columns>|<dimensions>`
+    InvalidCombined(String),
+    /// An indexed clue isn't of the form `<index>:<blocks>`
+    InvalidIndexedClue(String),
+    /// An indexed clue names a line past the end of the board
+    LineIndexOutOfRange { index: usize, num_lines: usize },
+    /// A ranged block size is empty or allows a block of no cells
+    InvalidClueRange { min: usize, max: usize },
+    /// A board with no rows or no columns was asked for
+    EmptyBoard { num_rows: usize, num_cols: usize },
+    /// A fill ratio isn't a probability between 0 and 1
+    InvalidFillRatio(f64),
+    /// A clue isn't a list of numbers
+    InvalidClue(String),
+    /// A pasted puzzle lacks its `rows:` or `cols:` section
+    MissingSection(&'static str)
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolverError::LineNotForced { index, axis } => {
+                write!(f, "{} {} is not yet determined", axis, index)
+            },
+            SolverError::GridSizeMismatch { expected, actual } => {
+                write!(f, "Expected a {}x{} grid, got {}x{}", expected.0, expected.1, actual.0, actual.1)
+            },
+            SolverError::LineMismatch { index, axis } => {
+                write!(f, "{} {} does not match its clue", axis, index)
+            },
+            SolverError::BufferTooSmall { required, actual } => {
+                write!(f, "Buffer holds {} cells, {} are needed", actual, required)
+            },
+            SolverError::ClueTotalMismatch { row_total, col_total } => {
+                write!(f, "Row clues add up to {} full cells but column clues to {}", row_total, col_total)
+            },
+            SolverError::Infeasible { index, axis } => {
+                write!(f, "{} {} is too short for its clue", axis, index)
+            },
+            SolverError::Contradiction => write!(f, "The clues contradict each other"),
+            SolverError::Ambiguous => write!(f, "The clues have more than one solution"),
+            SolverError::NoArrangement { index, axis } => {
+                write!(f, "{} {} can't be arranged to match the grid", axis, index)
+            },
+            SolverError::InvalidDimensions(s) => {
+                write!(f, "Invalid dimensions '{}', expected <columns>x<rows>", s)
+            },
+            SolverError::DimensionTooLarge { size, max } => {
+                write!(f, "Board side of {} exceeds the maximum of {}", size, max)
+            },
+            SolverError::InvalidCellValue(value) => write!(f, "{} is not a cell value", value),
+            SolverError::PositionCountMismatch { starts, lengths } => {
+                write!(f, "{} block starts given for {} block lengths", starts, lengths)
+            },
+            SolverError::BlockOverlap { block } => {
+                write!(f, "Block {} overlaps or touches the block before it", block)
+            },
+            SolverError::BlockOutOfRange { block, length } => {
+                write!(f, "Block {} runs past the end of a line of {} cells", block, length)
+            },
+            SolverError::InvalidRle(rle) => write!(f, "Invalid run-length encoded grid '{}'", rle),
+            SolverError::InvalidCombined(s) => {
+                write!(f, "Invalid puzzle '{}', expected <rows>|<columns>|<dimensions>", s)
+            },
+            SolverError::InvalidIndexedClue(s) => {
+                write!(f, "Invalid indexed clue '{}', expected <index>:<blocks>", s)
+            },
+            SolverError::LineIndexOutOfRange { index, num_lines } => {
+                write!(f, "Index {} is out of range for {} lines", index, num_lines)
+            },
+            SolverError::InvalidClueRange { min, max } => {
+                write!(f, "Invalid block size range {}-{}, expected 1 <= min <= max", min, max)
+            },
+            SolverError::EmptyBoard { num_rows, num_cols } => {
+                write!(f, "A board of {} rows and {} columns has no cells", num_rows, num_cols)
+            },
+            SolverError::InvalidFillRatio(ratio) => {
+                write!(f, "Invalid fill ratio {}, expected a value between 0 and 1", ratio)
+            },
+            SolverError::InvalidClue(clue) => write!(f, "Invalid clue '{}'", clue),
+            SolverError::MissingSection(section) => write!(f, "Missing '{}:' section", section)
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
@@ -0,0 +1,92 @@
+use crate::model::{CellState, Constraints, Dimensions, Line};
+
+/// Derives a puzzle from a picture drawn with `#` for full cells and `.` or space for empty ones.
+///
+/// Each line of `art` is a row. Shorter rows are padded with empty cells up to the widest row and
+/// blank lines before the first and after the last row are ignored.
+pub fn parse(art: &str) -> (Constraints, Dimensions) {
+    let rows = art
+        .trim_matches(|c| c == '\n' || c == '\r')
+        .lines()
+        .map(|line| {
+            line.chars()
+                .map(|c| match c {
+                    '#' => CellState::Full,
+                    _ => CellState::Empty
+                })
+                .collect::<Vec<CellState>>()
+        })
+        .collect::<Vec<Vec<CellState>>>();
+
+    let num_rows = rows.len();
+    let num_cols = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let rows = rows
+        .into_iter()
+        .map(|mut row| {
+            row.resize(num_cols, CellState::Empty);
+            row
+        })
+        .collect::<Vec<Vec<CellState>>>();
+
+    let row_constraints = rows
+        .iter()
+        .map(|row| Line::new(row.clone()).to_constraint())
+        .collect();
+    let col_constraints = (0..num_cols)
+        .map(|col_idx| {
+            let cells = rows.iter().map(|row| row[col_idx]).collect();
+            Line::new(cells).to_constraint()
+        })
+        .collect();
+
+    (Constraints::new(col_constraints, row_constraints), Dimensions::new(num_rows, num_cols))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Constraint;
+
+    #[test]
+    fn test_parse() {
+        let art = "
+.#.
+###
+#.#
+";
+        let (constraints, dimensions) = parse(art);
+
+        let row_constraints = vec![
+            Constraint::new(vec![1]),
+            Constraint::new(vec![3]),
+            Constraint::new(vec![1, 1])
+        ];
+        let col_constraints = vec![
+            Constraint::new(vec![2]),
+            Constraint::new(vec![2]),
+            Constraint::new(vec![2])
+        ];
+
+        assert_eq!(constraints, Constraints::new(col_constraints, row_constraints));
+        assert_eq!(dimensions, Dimensions::new(3, 3));
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        let (constraints, dimensions) = parse("#\n# #\n");
+
+        let row_constraints = vec![
+            Constraint::new(vec![1]),
+            Constraint::new(vec![1, 1])
+        ];
+        let col_constraints = vec![
+            Constraint::new(vec![2]),
+            Constraint::new(vec![]),
+            Constraint::new(vec![1])
+        ];
+
+        assert_eq!(constraints, Constraints::new(col_constraints, row_constraints));
+        assert_eq!(dimensions, Dimensions::new(2, 3));
+    }
+}
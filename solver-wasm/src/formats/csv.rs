@@ -0,0 +1,78 @@
+use std::num::ParseIntError;
+
+use crate::model::Constraint;
+
+/// Reads the row clues from a CSV with one line's clues per CSV row, e.g. `3,1,,` for a line
+/// with blocks of 3 and 1. Empty cells are skipped, so rows may have different numbers of clues,
+/// and a row of only empty cells (`,,`) or a single `0` is a line without blocks.
+///
+/// The column clues may follow in the same text after a blank row, only the clues before it are
+/// read.
+pub fn parse_rows(csv: &str) -> Result<Vec<Constraint>, ParseIntError> {
+    parse_block(blocks(csv).0)
+}
+
+/// Reads the column clues, in the same format as `parse_rows`. If `csv` contains a blank row the
+/// clues after it are read, otherwise `csv` is taken to be a separate file with only column clues.
+pub fn parse_cols(csv: &str) -> Result<Vec<Constraint>, ParseIntError> {
+    match blocks(csv) {
+        (_, Some(cols)) => parse_block(cols),
+        (rows, None) => parse_block(rows)
+    }
+}
+
+/// Splits `csv` at the first blank row, ignoring blank rows at the start and end.
+fn blocks(csv: &str) -> (&str, Option<&str>) {
+    let csv = csv.trim_matches(|c| c == '\n' || c == '\r');
+    let mut offset = 0;
+    for line in csv.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            return (&csv[..offset], Some(csv[offset + line.len()..].trim_start_matches(['\n', '\r'])))
+        }
+        offset += line.len();
+    }
+    (csv, None)
+}
+
+fn parse_block(block: &str) -> Result<Vec<Constraint>, ParseIntError> {
+    block
+        .lines()
+        .map(|line| {
+            let values = line
+                .split(',')
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+                .map(str::parse::<usize>)
+                .filter(|value| *value != Ok(0))
+                .collect::<Result<Vec<usize>, ParseIntError>>()?;
+            Ok(Constraint::new(values))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ragged() {
+        let csv = "3,1,,\n2,,,\n1,1,1,\n0,,,\n,,,\n\n1,\n2,1\n";
+
+        let row_constraints = vec![
+            Constraint::new(vec![3, 1]),
+            Constraint::new(vec![2]),
+            Constraint::new(vec![1, 1, 1]),
+            Constraint::new(vec![]),
+            Constraint::new(vec![])
+        ];
+        let col_constraints = vec![
+            Constraint::new(vec![1]),
+            Constraint::new(vec![2, 1])
+        ];
+
+        assert_eq!(parse_rows(csv), Ok(row_constraints));
+        assert_eq!(parse_cols(csv), Ok(col_constraints.clone()));
+        assert_eq!(parse_cols("1,\n2,1\n"), Ok(col_constraints));
+        assert!(parse_rows("1,x\n").is_err());
+    }
+}
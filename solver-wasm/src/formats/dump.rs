@@ -0,0 +1,157 @@
+use crate::error::SolverError;
+use crate::model::{CellState, Constraint, Constraints, Puzzle};
+
+/// A puzzle read from a dump, with whatever was already filled in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzleDump {
+    pub puzzle: Puzzle,
+    /// The `grid:` section, if the dump has one
+    pub grid: Option<Vec<Vec<CellState>>>
+}
+
+/// Reads a pasted puzzle made of a `rows:` section, a `cols:` section and optionally a `grid:`
+/// section with cells already filled in, e.g.
+///
+/// ```text
+/// rows: 1 1; 2
+/// cols: 1; 1; 2
+/// grid:
+/// #.?
+/// ???
+/// ```
+///
+/// Markers are matched case-insensitively (`columns:` works too) and anything before the first
+/// one is ignored. Clues are separated by `;` or, if a section has none, by line breaks, in
+/// which case `0` stands for a line without blocks. Blocks are separated by spaces or `,`. In
+/// the grid `#` is full, `.` or `x` empty and anything else unknown.
+///
+/// Fails with `MissingSection` or `InvalidClue` for unreadable clues, and with
+/// `GridSizeMismatch` if the grid doesn't have one row per row clue and one cell per column clue.
+pub fn parse(blob: &str) -> Result<PuzzleDump, SolverError> {
+    let mut rows = None;
+    let mut cols = None;
+    let mut grid = None;
+
+    let mut section: Option<(&str, String)> = None;
+    for line in blob.lines() {
+        let lower = line.trim_start().to_lowercase();
+        let marker = ["rows:", "cols:", "columns:", "grid:"]
+            .into_iter()
+            .find(|marker| lower.starts_with(marker));
+
+        match marker {
+            Some(marker) => {
+                if let Some((name, body)) = section.take() {
+                    store(name, body, &mut rows, &mut cols, &mut grid);
+                }
+                let rest = &line.trim_start()[marker.len()..];
+                section = Some((marker, format!("{}\n", rest)));
+            },
+            None => {
+                if let Some((_, body)) = section.as_mut() {
+                    body.push_str(line);
+                    body.push('\n');
+                }
+            }
+        }
+    }
+    if let Some((name, body)) = section {
+        store(name, body, &mut rows, &mut cols, &mut grid);
+    }
+
+    let rows = parse_clues(&rows.ok_or(SolverError::MissingSection("rows"))?)?;
+    let cols = parse_clues(&cols.ok_or(SolverError::MissingSection("cols"))?)?;
+
+    let grid = grid.map(|grid| parse_grid(&grid)).filter(|grid| !grid.is_empty());
+    if let Some(grid) = &grid {
+        let actual = (grid.len(), grid.iter().map(Vec::len).max().unwrap_or(0));
+        if grid.len() != rows.len() || grid.iter().any(|row| row.len() != cols.len()) {
+            return Err(SolverError::GridSizeMismatch { expected: (rows.len(), cols.len()), actual })
+        }
+    }
+
+    Ok(PuzzleDump { puzzle: Puzzle::new(Constraints::new(cols, rows)), grid })
+}
+
+fn store(marker: &str, body: String, rows: &mut Option<String>, cols: &mut Option<String>, grid: &mut Option<String>) {
+    match marker {
+        "rows:" => *rows = Some(body),
+        "grid:" => *grid = Some(body),
+        _ => *cols = Some(body)
+    }
+}
+
+fn parse_clues(section: &str) -> Result<Vec<Constraint>, SolverError> {
+    let clues = if section.contains(';') {
+        section.trim().split(';').collect::<Vec<&str>>()
+    } else {
+        section.lines().filter(|line| !line.trim().is_empty()).collect()
+    };
+
+    clues
+        .into_iter()
+        .map(|clue| {
+            let values = clue
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|value| !value.is_empty())
+                .map(str::parse::<usize>)
+                .filter(|value| *value != Ok(0))
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|_| SolverError::InvalidClue(clue.trim().to_string()))?;
+            Ok(Constraint::new(values))
+        })
+        .collect()
+}
+
+fn parse_grid(section: &str) -> Vec<Vec<CellState>> {
+    section
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.chars()
+                .map(|c| match c {
+                    '#' => CellState::Full,
+                    '.' | 'x' | 'X' => CellState::Empty,
+                    _ => CellState::Unknown
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_grid() {
+        let blob = "\
+Puzzle #12, shared from my phone
+Rows: 1 1; 2
+COLS:
+1
+0
+1,1
+grid:
+#.?
+??#
+";
+
+        let dump = parse(blob).unwrap();
+
+        assert_eq!((dump.puzzle.dimensions().num_rows(), dump.puzzle.dimensions().num_cols()), (2, 3));
+        assert_eq!(*dump.puzzle.constraints(), Constraints::new(
+            vec![Constraint::new(vec![1]), Constraint::new(vec![]), Constraint::new(vec![1, 1])],
+            vec![Constraint::new(vec![1, 1]), Constraint::new(vec![2])]
+        ));
+        assert_eq!(dump.grid, Some(vec![
+            vec![CellState::Full, CellState::Empty, CellState::Unknown],
+            vec![CellState::Unknown, CellState::Unknown, CellState::Full]
+        ]));
+
+        assert_eq!(parse("rows: 1\ncols: 1\n").unwrap().grid, None);
+        assert_eq!(parse("rows: 1\n").unwrap_err(), SolverError::MissingSection("cols"));
+        assert!(matches!(parse("rows: 1\ncols: 1\ngrid:\n##\n"), Err(SolverError::GridSizeMismatch { .. })));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::SolverError;
+use crate::model::{Board, CellState, Constraints, Dimensions};
+
+/// Derives a puzzle from a grayscale image, one byte per pixel row by row, `0` being black.
+/// Pixels darker than `threshold` become full cells and the rest empty ones, so raising the
+/// threshold fills in more of the picture.
+///
+/// Fails if the image has no pixels or `pixels` holds fewer than `width * height` bytes;
+/// anything past that is ignored.
+pub fn constraints_from_grayscale(pixels: &[u8], width: usize, height: usize, threshold: u8) -> Result<(Constraints, Dimensions), SolverError> {
+    if width == 0 || height == 0 {
+        return Err(SolverError::EmptyBoard { num_rows: height, num_cols: width })
+    }
+    // A size too large to fit a usize can't be held by any buffer either
+    let required = width
+        .checked_mul(height)
+        .ok_or(SolverError::BufferTooSmall { required: usize::MAX, actual: pixels.len() })?;
+    if pixels.len() < required {
+        return Err(SolverError::BufferTooSmall { required, actual: pixels.len() })
+    }
+
+    let grid = pixels[..required]
+        .chunks(width)
+        .map(|row| {
+            row.iter()
+                .map(|&pixel| if pixel < threshold { CellState::Full } else { CellState::Empty })
+                .collect()
+        })
+        .collect::<Vec<Vec<CellState>>>();
+
+    Ok(Board::clues_of(&grid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraints_from_grayscale() {
+        // A 3x2 image
+        let pixels = [
+            0, 100, 200,
+            100, 200, 0
+        ];
+
+        let dark = constraints_from_grayscale(&pixels, 3, 2, 50).unwrap();
+        let light = constraints_from_grayscale(&pixels, 3, 2, 150).unwrap();
+
+        assert_eq!(dark, crate::formats::art::parse("#..\n..#\n"));
+        assert_eq!(light, crate::formats::art::parse("##.\n#.#\n"));
+        assert_ne!(dark.0, light.0);
+        assert_eq!(
+            constraints_from_grayscale(&pixels, 3, 3, 50).unwrap_err(),
+            SolverError::BufferTooSmall { required: 9, actual: 6 }
+        );
+        assert_eq!(
+            constraints_from_grayscale(&pixels, usize::MAX, 2, 50).unwrap_err(),
+            SolverError::BufferTooSmall { required: usize::MAX, actual: 6 }
+        );
+        assert_eq!(
+            constraints_from_grayscale(&pixels, 0, 2, 50).unwrap_err(),
+            SolverError::EmptyBoard { num_rows: 2, num_cols: 0 }
+        );
+        assert_eq!(
+            constraints_from_grayscale(&pixels, 3, 0, 50).unwrap_err(),
+            SolverError::EmptyBoard { num_rows: 0, num_cols: 3 }
+        );
+    }
+}
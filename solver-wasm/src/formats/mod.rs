@@ -0,0 +1,5 @@
+pub mod art;
+pub mod csv;
+pub mod dump;
+pub mod image;
+pub mod non;
@@ -0,0 +1,143 @@
+use std::fmt;
+
+use crate::model::{Constraint, Constraints, Dimensions};
+
+/// Attribution fields of a `.non` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PuzzleMeta {
+    pub title: Option<String>,
+    /// The `by` field
+    pub author: Option<String>,
+    pub copyright: Option<String>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NonError {
+    /// A required field (`width`, `height`, `rows` or `columns`) is missing
+    MissingField(&'static str),
+    /// A clue line or a size isn't a list of numbers
+    InvalidLine(String)
+}
+
+impl fmt::Display for NonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NonError::MissingField(field) => write!(f, "Missing '{}'", field),
+            NonError::InvalidLine(line) => write!(f, "Invalid line '{}'", line)
+        }
+    }
+}
+
+impl std::error::Error for NonError {}
+
+/// Reads a puzzle in the `.non` format: `width` and `height` fields, followed by a `rows` and
+/// a `columns` section with one line's clues per line, e.g. `3,1`. A `0` is a line without
+/// blocks.
+///
+/// `title`, `by` and `copyright` are returned as `PuzzleMeta`, with surrounding quotes removed.
+/// Lines starting with `#`, blank lines and any other field (e.g. `goal` or `catalogue`) are
+/// skipped.
+pub fn parse(non: &str) -> Result<(Constraints, Dimensions, PuzzleMeta), NonError> {
+    let mut meta = PuzzleMeta::default();
+    let mut width = None;
+    let mut height = None;
+    let mut rows = None;
+    let mut cols = None;
+
+    let mut lines = non
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    while let Some(line) = lines.next() {
+        let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let value = value.trim().trim_matches('"');
+
+        match key {
+            "title" => meta.title = Some(value.to_string()),
+            "by" => meta.author = Some(value.to_string()),
+            "copyright" => meta.copyright = Some(value.to_string()),
+            "width" => width = Some(parse_size(line, value)?),
+            "height" => height = Some(parse_size(line, value)?),
+            "rows" => {
+                let num_rows = height.ok_or(NonError::MissingField("height"))?;
+                rows = Some(parse_clues(&mut lines, num_rows)?);
+            },
+            "columns" => {
+                let num_cols = width.ok_or(NonError::MissingField("width"))?;
+                cols = Some(parse_clues(&mut lines, num_cols)?);
+            },
+            _ => {}
+        }
+    }
+
+    let rows = rows.ok_or(NonError::MissingField("rows"))?;
+    let cols = cols.ok_or(NonError::MissingField("columns"))?;
+    let dimensions = Dimensions::new(rows.len(), cols.len());
+    Ok((Constraints::new(cols, rows), dimensions, meta))
+}
+
+fn parse_size(line: &str, value: &str) -> Result<usize, NonError> {
+    value.parse().map_err(|_| NonError::InvalidLine(line.to_string()))
+}
+
+/// Reads the next `count` lines as clues.
+fn parse_clues<'a>(lines: &mut impl Iterator<Item = &'a str>, count: usize) -> Result<Vec<Constraint>, NonError> {
+    (0..count)
+        .map(|_| {
+            let line = lines.next().ok_or(NonError::MissingField("clue"))?;
+            let values = line
+                .split(',')
+                .map(str::trim)
+                .map(str::parse::<usize>)
+                .filter(|value| *value != Ok(0))
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|_| NonError::InvalidLine(line.to_string()))?;
+            Ok(Constraint::new(values))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_meta() {
+        let non = "\
+# Downloaded from a public library
+catalogue \"test-1\"
+title \"Corner\"
+by \"Jane Doe\"
+copyright \"CC BY 4.0\"
+width 3
+height 2
+
+rows
+2
+# The second row is empty
+0
+
+columns
+1
+1
+0
+goal \"110000\"
+";
+
+        let (constraints, dimensions, meta) = parse(non).unwrap();
+
+        assert_eq!(meta, PuzzleMeta {
+            title: Some("Corner".to_string()),
+            author: Some("Jane Doe".to_string()),
+            copyright: Some("CC BY 4.0".to_string())
+        });
+        assert_eq!((dimensions.num_rows(), dimensions.num_cols()), (2, 3));
+        assert_eq!(constraints, Constraints::new(
+            vec![Constraint::new(vec![1]), Constraint::new(vec![1]), Constraint::new(vec![])],
+            vec![Constraint::new(vec![2]), Constraint::new(vec![])]
+        ));
+
+        assert_eq!(parse("width 1\nheight 1\nrows\n1\n").unwrap_err(), NonError::MissingField("columns"));
+        assert!(matches!(parse("width 1\nheight 1\nrows\nx\n"), Err(NonError::InvalidLine(_))));
+    }
+}
@@ -1,8 +1,25 @@
 use wasm_bindgen::prelude::*;
 
-use crate::model::{Constraint, Constraints, Dimensions, Board};
+use crate::error::SolverError;
+use crate::model::{Axis, CellState, Constraint, Constraints, Dimensions, Board, Line, Puzzle, SolveOutcome, SolveResult, SolveStats};
 
-mod model;
+#[cfg(feature = "corpus")]
+pub mod corpus;
+pub mod error;
+pub mod formats;
+pub mod model;
+
+/// Largest number of rows or columns accepted from a dimension string.
+pub const MAX_DIMENSION: usize = 1000;
+
+/// Which way `solve_auto` read the clues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// Rows and columns as given
+    AsGiven,
+    /// The row clues were used as column clues and vice versa
+    Swapped
+}
 
 #[wasm_bindgen]
 extern "C" {
@@ -10,37 +27,344 @@ extern "C" {
 }
 
 // TODO: Add support for sending state
+/// Solves a puzzle and returns the board as a string.
+///
+/// - `constraints_x_str`: the row clues, top to bottom (`hintsX` in the benchmark data)
+/// - `constraints_y_str`: the column clues, left to right (`hintsY` in the benchmark data)
+/// - `dimensions`: `"<columns>x<rows>"`
+///
+/// Lines are separated by `;` and blocks within a line by `,`. Prefer `solve_rows_cols` or
+/// `solve_cols_rows`, whose names spell out the argument order.
+///
+/// Returns `"Error: ..."` if the dimensions are malformed or larger than `MAX_DIMENSION`.
 #[wasm_bindgen]
 pub fn solve(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str) -> String {
-    let constraints_row = parse_array_string(constraints_y_str)
+    match solve_puzzle(constraints_x_str, constraints_y_str, dimensions) {
+        Ok((board, _)) => board.to_string(),
+        Err(err) => format!("Error: {}", err)
+    }
+}
+
+/// Same as `solve`, with the row clues first and the column clues second.
+#[wasm_bindgen]
+pub fn solve_rows_cols(row_clues: &str, col_clues: &str, dimensions: &str) -> String {
+    solve(row_clues, col_clues, dimensions)
+}
+
+/// Same as `solve`, with the column clues first and the row clues second.
+#[wasm_bindgen]
+pub fn solve_cols_rows(col_clues: &str, row_clues: &str, dimensions: &str) -> String {
+    solve(row_clues, col_clues, dimensions)
+}
+
+/// Same as `solve`, with the row clues, the column clues and the dimensions combined into a
+/// single `"<rows>|<columns>|<dimensions>"` string, e.g. `"1,1;2|1;1;2|3x2"`.
+#[wasm_bindgen]
+pub fn solve_combined(blob: &str) -> String {
+    match blob.split('|').collect::<Vec<&str>>()[..] {
+        [row_clues, col_clues, dimensions] => solve(row_clues, col_clues, dimensions),
+        _ => format!("Error: {}", SolverError::InvalidCombined(blob.to_string()))
+    }
+}
+
+/// Generates a random puzzle of `rows` by `cols` where each cell is full with a probability of
+/// `fill_ratio`, in the same layout as `Board::to_puzzle_json` with the solution filled in. The
+/// same `seed` gives the same puzzle on every platform, e.g. for a daily puzzle. Sides of zero
+/// or larger than `MAX_DIMENSION`, and fill ratios outside [0, 1], give `{"error": "..."}`.
+#[wasm_bindgen]
+pub fn generate(rows: usize, cols: usize, fill_ratio: f64, seed: u64) -> String {
+    if let Some(size) = [rows, cols].into_iter().find(|&size| size > MAX_DIMENSION) {
+        return error_json(&SolverError::DimensionTooLarge { size, max: MAX_DIMENSION })
+    }
+    if rows == 0 || cols == 0 {
+        return error_json(&SolverError::EmptyBoard { num_rows: rows, num_cols: cols })
+    }
+    // Also rejects NaN, which fails every comparison
+    if !(0.0..=1.0).contains(&fill_ratio) {
+        return error_json(&SolverError::InvalidFillRatio(fill_ratio))
+    }
+    Board::generate(rows, cols, fill_ratio, seed).to_puzzle_json()
+}
+
+/// Solves a puzzle like `solve`, guessing cells if propagation alone stalls, and returns e.g.
+/// `{"outcome": "solved", "used_search": false, "solution": [[2,1,2],[1,2,2]]}`. The solution
+/// encodes cells like the benchmark data (`2` for full, `1` for empty, `0` for undetermined) and
+/// `used_search` tells whether guessing was needed, so callers can keep to puzzles that logic
+/// alone solves. Invalid dimensions give `{"error": "..."}`.
+#[wasm_bindgen]
+pub fn solve_json(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str) -> String {
+    let dimensions = match parse_dim_string(dimensions, MAX_DIMENSION) {
+        Ok(dimensions) => dimensions,
+        Err(err) => return error_json(&err)
+    };
+    let constraints = to_constraints(
+        parse_array_string(constraints_x_str),
+        parse_array_string(constraints_y_str)
+    );
+    if let Err(err) = constraints.check_dimensions(&dimensions) {
+        return error_json(&err)
+    }
+
+    let result = Puzzle::new(constraints).solve_any();
+    let outcome = match result.outcome {
+        SolveOutcome::Solved => "solved",
+        SolveOutcome::Stalled => "stalled",
+        SolveOutcome::Contradiction { .. } => "contradiction"
+    };
+    let solution = result.grid
+        .iter()
+        .map(|row| {
+            let cells = row
+                .iter()
+                .map(|cell| match cell {
+                    CellState::Full => "2",
+                    CellState::Empty => "1",
+                    _ => "0"
+                })
+                .collect::<Vec<&str>>();
+            format!("[{}]", cells.join(","))
+        })
+        .collect::<Vec<String>>();
+
+    format!(
+        "{{\"outcome\": \"{}\", \"used_search\": {}, \"solution\": [{}]}}",
+        outcome,
+        result.stats.used_search,
+        solution.join(",")
+    )
+}
+
+/// Native counterpart of `solve`, taking the clues in the same format. Returns the solved board
+/// along with how the solve ended, so the grid, stats and rendering are all available.
+pub fn solve_puzzle(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str) -> Result<(Board, SolveOutcome), SolverError> {
+    let dimensions = parse_dim_string(dimensions, MAX_DIMENSION)?;
+
+    solve_arrays(
+        parse_array_string(constraints_x_str),
+        parse_array_string(constraints_y_str),
+        dimensions
+    )
+}
+
+/// Like `solve_puzzle`, but if the clues can't be solved as given, e.g. because they don't match
+/// the dimensions or contradict each other, tries again with the row and column clues swapped.
+/// Returns the orientation that worked, or the error from the clues as given if neither did.
+///
+/// On a square board swapped clues describe the transposed picture, which is usually just as
+/// solvable, so a swap can only be caught when it makes the clues fail.
+pub fn solve_auto(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str) -> Result<(Board, SolveOutcome, Orientation), SolverError> {
+    let dimensions = parse_dim_string(dimensions, MAX_DIMENSION)?;
+    let constraints_x = parse_array_string(constraints_x_str);
+    let constraints_y = parse_array_string(constraints_y_str);
+
+    let err = match try_solve_arrays(constraints_x.clone(), constraints_y.clone(), dimensions) {
+        Ok((board, outcome)) => return Ok((board, outcome, Orientation::AsGiven)),
+        Err(err) => err
+    };
+    match try_solve_arrays(constraints_y, constraints_x, dimensions) {
+        Ok((board, outcome)) => Ok((board, outcome, Orientation::Swapped)),
+        Err(_) => Err(err)
+    }
+}
+
+/// Solves a puzzle from the block sizes themselves, the row clues from top to bottom and the
+/// column clues from left to right. The board size follows from the number of clues.
+pub fn solve_vecs(row_clues: Vec<Vec<usize>>, col_clues: Vec<Vec<usize>>) -> SolveResult {
+    Puzzle::new(to_constraints(row_clues, col_clues)).solve()
+}
+
+/// Like `solve_puzzle`, but returns the board as `solve` renders it along with the solver's
+/// work counters.
+pub fn solve_with_stats(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str) -> Result<(String, SolveStats), SolverError> {
+    let (board, _) = solve_puzzle(constraints_x_str, constraints_y_str, dimensions)?;
+    Ok((board.to_string(), board.stats().clone()))
+}
+
+/// Like `solve`, but every clue is prefixed with the index of its line (e.g. `"2:1,3;0:5"`).
+/// Lines may be given in any order and lines that are left out get an empty clue.
+#[wasm_bindgen]
+pub fn solve_indexed(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str) -> String {
+    let dimensions = match parse_dim_string(dimensions, MAX_DIMENSION) {
+        Ok(dimensions) => dimensions,
+        Err(err) => return format!("Error: {}", err)
+    };
+
+    let solved = parse_indexed_array_string(constraints_x_str, dimensions.num_rows())
+        .and_then(|constraints_x| {
+            let constraints_y = parse_indexed_array_string(constraints_y_str, dimensions.num_cols())?;
+            solve_arrays(constraints_x, constraints_y, dimensions)
+        });
+    match solved {
+        Ok((board, _)) => board.to_string(),
+        Err(err) => format!("Error: {}", err)
+    }
+}
+
+/// Estimates how many candidate lines `solve` would generate for the puzzle and roughly how many
+/// bytes they would take, e.g. `{"candidates": 3127, "bytes": 121953}`. Lets a front-end warn
+/// before attempting a board that would exhaust memory. Invalid dimensions give
+/// `{"error": "..."}`.
+#[wasm_bindgen]
+pub fn estimate_memory(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str) -> String {
+    let dimensions = match parse_dim_string(dimensions, MAX_DIMENSION) {
+        Ok(dimensions) => dimensions,
+        Err(err) => return error_json(&err)
+    };
+    let constraints = to_constraints(
+        parse_array_string(constraints_x_str),
+        parse_array_string(constraints_y_str)
+    );
+    if let Err(err) = constraints.check_dimensions(&dimensions) {
+        return error_json(&err)
+    }
+
+    let (candidates, bytes) = constraints.memory_estimate(&dimensions);
+    format!("{{\"candidates\": {}, \"bytes\": {}}}", candidates, bytes)
+}
+
+/// Shows what the overlap technique alone reveals for a single line with the clue `clue_str`
+/// (blocks separated by `,`). Returns a JSON array with one entry per cell: `1` for full, `0` for
+/// empty and `-1` for undetermined, or `null` if the clue doesn't fit in `length` cells. A clue
+/// that isn't a list of numbers, or a `length` larger than `MAX_DIMENSION`, gives
+/// `{"error": "..."}`.
+#[wasm_bindgen]
+pub fn line_overlap(clue_str: &str, length: usize) -> String {
+    if length > MAX_DIMENSION {
+        return error_json(&SolverError::DimensionTooLarge { size: length, max: MAX_DIMENSION })
+    }
+    let values = match clue_str.split(',')
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::parse::<usize>)
+        .collect::<Result<Vec<usize>, _>>()
+    {
+        Ok(values) => values,
+        Err(_) => return error_json(&SolverError::InvalidClue(clue_str.to_string()))
+    };
+
+    match Line::overlap(length, &Constraint::new(values)) {
+        Some(line) => {
+            let cells = line.cells()
+                .iter()
+                .map(|&cell| i8::from(cell).to_string())
+                .collect::<Vec<String>>();
+            format!("[{}]", cells.join(","))
+        },
+        None => "null".to_string()
+    }
+}
+
+/// Solves a puzzle like `solve` and compares the result with `expected_json`, a JSON array of
+/// rows encoded like the `solution` field of the benchmark data (`2` for full, `1` for empty).
+/// Returns e.g. `{"match": false, "mismatches": [[0,2],[1,0]]}`, listing every `[row, col]` the
+/// solver left unknown or filled differently. Invalid dimensions give `{"error": "..."}`.
+#[wasm_bindgen]
+pub fn solve_and_check(constraints_x_str: &str, constraints_y_str: &str, dimensions: &str, expected_json: &str) -> String {
+    let dimensions = match parse_dim_string(dimensions, MAX_DIMENSION) {
+        Ok(dimensions) => dimensions,
+        Err(err) => return error_json(&err)
+    };
+    let constraints = to_constraints(
+        parse_array_string(constraints_x_str),
+        parse_array_string(constraints_y_str)
+    );
+    if let Err(err) = constraints.check_dimensions(&dimensions) {
+        return error_json(&err)
+    }
+
+    let mut board = Board::new(constraints, dimensions);
+    board.solve();
+
+    let expected = parse_grid_json(expected_json);
+    let mismatches = board.grid()
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            let expected = &expected;
+            cells.iter().enumerate().filter_map(move |(col, &cell)| {
+                let expected = match expected.get(row).and_then(|cells| cells.get(col)) {
+                    Some(2) => CellState::Full,
+                    Some(_) => CellState::Empty,
+                    None => CellState::Unknown
+                };
+                (cell != expected || cell.is_unknown()).then(|| format!("[{},{}]", row, col))
+            })
+        })
+        .collect::<Vec<String>>();
+
+    format!("{{\"match\": {}, \"mismatches\": [{}]}}", mismatches.is_empty(), mismatches.join(","))
+}
+
+/// Solves the clues, failing if there isn't one clue per row and column of `dimensions`.
+fn solve_arrays(constraints_x: Vec<Vec<usize>>, constraints_y: Vec<Vec<usize>>, dimensions: Dimensions) -> Result<(Board, SolveOutcome), SolverError> {
+    let constraints = to_constraints(constraints_x, constraints_y);
+    constraints.check_dimensions(&dimensions)?;
+
+    let mut board = Board::new(constraints, dimensions);
+    let outcome = board.solve();
+    Ok((board, outcome))
+}
+
+/// Like `solve_arrays`, but also rejects clues that can't fit the board and turns a
+/// contradiction into an error.
+fn try_solve_arrays(constraints_x: Vec<Vec<usize>>, constraints_y: Vec<Vec<usize>>, dimensions: Dimensions) -> Result<(Board, SolveOutcome), SolverError> {
+    let constraints = to_constraints(constraints_x, constraints_y);
+    constraints.check_dimensions(&dimensions)?;
+    constraints.check_totals()?;
+
+    let mut board = Board::new(constraints, dimensions);
+    let (rows_fit, cols_fit) = board.line_feasibility();
+    if let Some(index) = rows_fit.iter().position(|fits| !fits) {
+        return Err(SolverError::Infeasible { index, axis: Axis::Row })
+    }
+    if let Some(index) = cols_fit.iter().position(|fits| !fits) {
+        return Err(SolverError::Infeasible { index, axis: Axis::Col })
+    }
+
+    match board.solve() {
+        SolveOutcome::Contradiction { index, axis } => Err(SolverError::NoArrangement { index, axis }),
+        outcome => Ok((board, outcome))
+    }
+}
+
+fn to_constraints(constraints_x: Vec<Vec<usize>>, constraints_y: Vec<Vec<usize>>) -> Constraints {
+    // x holds the row clues and y the column clues
+    let constraints_row = constraints_x
         .into_iter()
         .map(|values|{
             Constraint::new(values)
         })
         .collect();
-    let constraints_col = parse_array_string(constraints_x_str)
+    let constraints_col = constraints_y
         .into_iter()
         .map(|values|{
             Constraint::new(values)
         })
         .collect();
-    let constraints = Constraints::new(constraints_row, constraints_col);
-
-    let dimensions = parse_dim_string(dimensions);
-
-    let mut board = Board::new(constraints, dimensions);
-    board.solve();
-    
-    // TODO: Return board state properly somehow
-    board.to_string()
+    Constraints::new(constraints_col, constraints_row)
 }
 
-fn parse_dim_string(s: &str) -> Dimensions {
-    let parts = s.split("x").collect::<Vec<&str>>();
+/// Parses `"<columns>x<rows>"`, rejecting sides larger than `max` before anything is allocated.
+fn parse_dim_string(s: &str, max: usize) -> Result<Dimensions, SolverError> {
+    let invalid = || SolverError::InvalidDimensions(s.to_string());
+    let (num_cols, num_rows) = s.split_once('x').ok_or_else(invalid)?;
+
+    let parse_side = |side: &str| {
+        let side = side.trim();
+        if side.is_empty() || !side.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        // Anything too long to fit a usize is also over the maximum
+        match side.parse::<usize>() {
+            Ok(size) if size <= max => Ok(size),
+            Ok(size) => Err(SolverError::DimensionTooLarge { size, max }),
+            Err(_) => Err(SolverError::DimensionTooLarge { size: usize::MAX, max })
+        }
+    };
 
-    let num_cols = parts[0].parse().unwrap();
-    let num_rows = parts[1].parse().unwrap();
-    Dimensions::new(num_rows, num_cols)
+    let num_cols = parse_side(num_cols)?;
+    let num_rows = parse_side(num_rows)?;
+    Ok(Dimensions::new(num_rows, num_cols))
 }
 
 fn parse_array_string(s: &str) -> Vec<Vec<usize>> {
@@ -53,6 +377,53 @@ fn parse_array_string(s: &str) -> Vec<Vec<usize>> {
         .collect()
 }
 
+fn parse_indexed_array_string(s: &str, length: usize) -> Result<Vec<Vec<usize>>, SolverError> {
+    let mut arrays = vec![Vec::new(); length];
+    for entry in s.split(';').filter(|entry| !entry.trim().is_empty()) {
+        let invalid = || SolverError::InvalidIndexedClue(entry.to_string());
+        let (idx, values) = entry.split_once(':').ok_or_else(invalid)?;
+        let idx = idx.trim().parse::<usize>().map_err(|_| invalid())?;
+        if idx >= length {
+            return Err(SolverError::LineIndexOutOfRange { index: idx, num_lines: length })
+        }
+
+        arrays[idx] = values.split(',')
+            .filter_map(|n| n.trim().parse::<usize>().ok())
+            .collect();
+    }
+    Ok(arrays)
+}
+
+/// Renders `err` as `{"error": "..."}`, escaping the message since it may quote user input.
+fn error_json(err: &SolverError) -> String {
+    let mut message = String::new();
+    for c in err.to_string().chars() {
+        match c {
+            '"' => message.push_str("\\\""),
+            '\\' => message.push_str("\\\\"),
+            '\n' => message.push_str("\\n"),
+            c if c.is_control() => message.push_str(&format!("\\u{:04x}", c as u32)),
+            c => message.push(c)
+        }
+    }
+    format!("{{\"error\": \"{}\"}}", message)
+}
+
+fn parse_grid_json(s: &str) -> Vec<Vec<u8>> {
+    // Only nested arrays of numbers are expected, so the brackets can be treated as separators
+    s.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split("],")
+        .map(|row| {
+            row.trim_matches(|c: char| c == '[' || c == ']' || c.is_whitespace())
+                .split(',')
+                .filter_map(|n| n.trim().parse::<u8>().ok())
+                .collect()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,6 +443,232 @@ mod tests {
         assert_eq!(arr2, vec![vec![7, 8], vec![9, 10, 11]]);
     }
 
+    #[test]
+    fn test_parse_indexed_array_string() {
+        let in_order = parse_indexed_array_string("0:1,2;1:3;2:4,5", 3);
+        let out_of_order = parse_indexed_array_string("2:1,3;0:5;1:2", 3);
+        let missing_index = parse_indexed_array_string("2:1,3;0:5", 4);
+
+        assert_eq!(in_order, Ok(vec![vec![1, 2], vec![3], vec![4, 5]]));
+        assert_eq!(out_of_order, Ok(vec![vec![5], vec![2], vec![1, 3]]));
+        assert_eq!(missing_index, Ok(vec![vec![5], vec![], vec![1, 3], vec![]]));
+        assert_eq!(
+            parse_indexed_array_string("9:1", 2),
+            Err(SolverError::LineIndexOutOfRange { index: 9, num_lines: 2 })
+        );
+        assert_eq!(parse_indexed_array_string("a:1", 2), Err(SolverError::InvalidIndexedClue("a:1".to_string())));
+        assert_eq!(parse_indexed_array_string("1", 2), Err(SolverError::InvalidIndexedClue("1".to_string())));
+
+        assert_eq!(solve_indexed("9:1", "0:1", "2x2"), "Error: Index 9 is out of range for 2 lines");
+        assert_eq!(solve_indexed("0:1", "a:1", "2x2"), "Error: Invalid indexed clue 'a:1', expected <index>:<blocks>");
+    }
+
+    #[test]
+    fn test_parse_dim_string() {
+        let dimensions = parse_dim_string("3x2", MAX_DIMENSION).unwrap();
+        assert_eq!((dimensions.num_rows(), dimensions.num_cols()), (2, 3));
+
+        assert_eq!(
+            parse_dim_string("1001x5", MAX_DIMENSION).unwrap_err(),
+            SolverError::DimensionTooLarge { size: 1001, max: MAX_DIMENSION }
+        );
+        assert_eq!(
+            parse_dim_string("99999999999999999999999x5", MAX_DIMENSION).unwrap_err(),
+            SolverError::DimensionTooLarge { size: usize::MAX, max: MAX_DIMENSION }
+        );
+        assert_eq!(
+            parse_dim_string("10x-5", MAX_DIMENSION).unwrap_err(),
+            SolverError::InvalidDimensions("10x-5".to_string())
+        );
+        assert_eq!(
+            parse_dim_string("ten", MAX_DIMENSION).unwrap_err(),
+            SolverError::InvalidDimensions("ten".to_string())
+        );
+        assert!(solve("1", "1", "5x99999999999").starts_with("Error: "));
+    }
+
+    #[test]
+    fn test_estimate_memory() {
+        let hints_x_str = "4,1,2;1,1,3;1,2;1,1,1,1;1,3;2,4;1,1,5;1,3;1,1,2,2;1,1"; 
+        let hints_y_str = "1,1,1,2;1,2;1,1,1;2,1,1;1;5,1,1;3,1;2,4;3,5;2,1,4";
+
+        let estimate = estimate_memory(hints_x_str, hints_y_str, "10x10");
+
+        let (_, stats) = solve_with_stats(hints_x_str, hints_y_str, "10x10").unwrap();
+        let bytes_per_line = std::mem::size_of::<model::Line>() + 10;
+        assert_eq!(
+            estimate,
+            format!("{{\"candidates\": {}, \"bytes\": {}}}", stats.candidates_generated, stats.candidates_generated * bytes_per_line)
+        );
+    }
+
+    #[test]
+    fn test_orientation() {
+        // 3 columns and 2 rows:
+        // █░█
+        // ░██
+        let row_clues = "1,1;2";
+        let col_clues = "1;1;2";
+        let expected = "█░█\n░██\n\n";
+
+        assert_eq!(solve_rows_cols(row_clues, col_clues, "3x2"), expected);
+        assert_eq!(solve_cols_rows(col_clues, row_clues, "3x2"), expected);
+        assert_eq!(solve(row_clues, col_clues, "3x2"), expected);
+    }
+
+    #[test]
+    fn test_solve_combined() {
+        assert_eq!(solve_combined("1,1;2|1;1;2|3x2"), solve("1,1;2", "1;1;2", "3x2"));
+        assert_eq!(
+            solve_combined("1,1;2|3x2"),
+            "Error: Invalid puzzle '1,1;2|3x2', expected <rows>|<columns>|<dimensions>"
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let puzzle = generate(10, 12, 0.6, 20261016);
+
+        assert_eq!(generate(10, 12, 0.6, 20261016), puzzle);
+        assert_ne!(generate(10, 12, 0.6, 20261017), puzzle);
+        assert!(puzzle.contains("\"solution\""));
+        assert!(generate(1001, 5, 0.5, 1).starts_with("{\"error\": "));
+    }
+
+    #[test]
+    fn test_generate_empty_board() {
+        assert_eq!(generate(5, 0, 0.5, 1), "{\"error\": \"A board of 5 rows and 0 columns has no cells\"}");
+        assert_eq!(generate(0, 5, 0.5, 1), "{\"error\": \"A board of 0 rows and 5 columns has no cells\"}");
+    }
+
+    #[test]
+    fn test_generate_invalid_fill_ratio() {
+        for ratio in [f64::NAN, f64::INFINITY, -0.1, 1.5] {
+            assert!(generate(5, 5, ratio, 1).starts_with("{\"error\": \"Invalid fill ratio"), "{}", ratio);
+        }
+        assert!(generate(5, 5, 0.0, 1).contains("\"solution\""));
+        assert!(generate(5, 5, 1.0, 1).contains("\"solution\""));
+    }
+
+    #[test]
+    fn test_solve_json() {
+        assert_eq!(
+            solve_json("1,1;2", "1;1;2", "3x2"),
+            "{\"outcome\": \"solved\", \"used_search\": false, \"solution\": [[2,1,2],[1,2,2]]}"
+        );
+        // #..#
+        // .#.#
+        // .#..
+        // #..#
+        assert_eq!(
+            solve_json("1,1;1,1;1;1,1", "1,1;2;;2,1", "4x4"),
+            "{\"outcome\": \"solved\", \"used_search\": true, \"solution\": [[2,1,1,2],[1,2,1,2],[1,2,1,1],[2,1,1,2]]}"
+        );
+        assert!(solve_json("1", "1", "2x2").starts_with("{\"error\": "));
+        let json = serde_json::from_str::<serde_json::Value>(&solve_json("1", "1", "2\"x2")).unwrap();
+        assert_eq!(json["error"], "Invalid dimensions '2\"x2', expected <columns>x<rows>");
+    }
+
+    #[test]
+    fn test_error_json() {
+        let dimensions = "5\"\\x5";
+        let err = SolverError::InvalidDimensions(dimensions.to_string());
+
+        for json in [estimate_memory("1", "1", dimensions), solve_and_check("1", "1", dimensions, "[[2]]")] {
+            let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+            assert_eq!(json["error"], err.to_string());
+        }
+        let json = serde_json::from_str::<serde_json::Value>(&error_json(&SolverError::InvalidRle("a\tb\n".to_string()))).unwrap();
+        assert_eq!(json["error"], "Invalid run-length encoded grid 'a\tb\n'");
+    }
+
+    #[test]
+    fn test_solve_and_check() {
+        // █░█
+        // ░██
+        let row_clues = "1,1;2";
+        let col_clues = "1;1;2";
+
+        assert_eq!(
+            solve_and_check(row_clues, col_clues, "3x2", "[[2,1,2],[1,2,2]]"),
+            "{\"match\": true, \"mismatches\": []}"
+        );
+        assert_eq!(
+            solve_and_check(row_clues, col_clues, "3x2", "[[2, 2, 1], [1, 2, 2]]"),
+            "{\"match\": false, \"mismatches\": [[0,1],[0,2]]}"
+        );
+    }
+
+    #[test]
+    fn test_solve_puzzle() {
+        let (board, outcome) = solve_puzzle("1,1;2", "1;1;2", "3x2").unwrap();
+
+        assert_eq!(outcome, SolveOutcome::Solved);
+        assert_eq!(board.to_string(), solve("1,1;2", "1;1;2", "3x2"));
+        assert_eq!(board.cell(0, 1), CellState::Empty);
+        assert!(solve_puzzle("1", "1", "1xone").is_err());
+        assert_eq!(
+            solve_puzzle("1", "1", "5x5").err(),
+            Some(SolverError::GridSizeMismatch { expected: (5, 5), actual: (1, 1) })
+        );
+    }
+
+    #[test]
+    fn test_clue_count_mismatch() {
+        assert_eq!(solve("1", "1", "5x5"), "Error: Expected a 5x5 grid, got 1x1");
+        assert_eq!(solve_indexed("0:1", "0:1", "2x2"), solve("1;", "1;", "2x2"));
+        assert_eq!(solve_combined("1|1|5x5"), "Error: Expected a 5x5 grid, got 1x1");
+        assert_eq!(estimate_memory("1", "1", "5x5"), "{\"error\": \"Expected a 5x5 grid, got 1x1\"}");
+        assert_eq!(solve_and_check("1", "1", "5x5", "[[2]]"), "{\"error\": \"Expected a 5x5 grid, got 1x1\"}");
+        assert_eq!(solve_json("1", "1", "5x5"), "{\"error\": \"Expected a 5x5 grid, got 1x1\"}");
+    }
+
+    #[test]
+    fn test_solve_vecs() {
+        let result = solve_vecs(vec![vec![1, 1], vec![2]], vec![vec![1], vec![1], vec![2]]);
+        let (board, outcome) = solve_puzzle("1,1;2", "1;1;2", "3x2").unwrap();
+
+        assert_eq!(result.outcome, outcome);
+        assert_eq!(result.grid, board.grid());
+        assert_eq!(result.grid[0], vec![CellState::Full, CellState::Empty, CellState::Full]);
+    }
+
+    #[test]
+    fn test_solve_auto() {
+        // █░█
+        // ░██
+        let row_clues = "1,1;2";
+        let col_clues = "1;1;2";
+
+        let (board, outcome, orientation) = solve_auto(row_clues, col_clues, "3x2").unwrap();
+        assert_eq!((outcome, orientation), (SolveOutcome::Solved, Orientation::AsGiven));
+        assert_eq!(board.to_string(), solve(row_clues, col_clues, "3x2"));
+
+        let (board, outcome, orientation) = solve_auto(col_clues, row_clues, "3x2").unwrap();
+        assert_eq!((outcome, orientation), (SolveOutcome::Solved, Orientation::Swapped));
+        assert_eq!(board.to_string(), solve(row_clues, col_clues, "3x2"));
+
+        assert!(matches!(
+            solve_auto("3;3", "1;1;2", "3x2"),
+            Err(SolverError::ClueTotalMismatch { row_total: 6, col_total: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_line_overlap() {
+        assert_eq!(line_overlap("8", 10), "[-1,-1,1,1,1,1,1,1,-1,-1]");
+        assert_eq!(line_overlap("", 3), "[0,0,0]");
+        assert_eq!(line_overlap("2,2", 4), "null");
+    }
+
+    #[test]
+    fn test_line_overlap_invalid_input() {
+        assert_eq!(line_overlap("8,x", 10), "{\"error\": \"Invalid clue '8,x'\"}");
+        assert_eq!(line_overlap("-1", 10), "{\"error\": \"Invalid clue '-1'\"}");
+        assert_eq!(line_overlap("1,1,1,1,1", 1001), "{\"error\": \"Board side of 1001 exceeds the maximum of 1000\"}");
+        assert_eq!(line_overlap("1", usize::MAX), format!("{{\"error\": \"Board side of {} exceeds the maximum of 1000\"}}", usize::MAX));
+    }
+
     #[test]
     fn test_solve() {
         // let hints_x_str = "1,2;4;2,1;1,1;1"; 
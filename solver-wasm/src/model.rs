@@ -1,13 +1,49 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::fmt;
-use std::ops::{BitAnd, BitOr};
+use std::hash::{Hash, Hasher};
+use std::num::ParseIntError;
+use std::ops::{BitAnd, BitOr, Range, RangeInclusive};
 use std::iter::zip;
 use itertools::Itertools;
+use unicode_width::UnicodeWidthStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::SolverError;
 
 
 // TODO: General cleanup
 // TODO: Use bits to represent board state
 
 
+/// Direction of a line on the board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    Row,
+    Col
+}
+
+impl Axis {
+    /// The crossing direction.
+    pub fn other(&self) -> Self {
+        match self {
+            Axis::Row => Axis::Col,
+            Axis::Col => Axis::Row
+        }
+    }
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Axis::Row => write!(f, "Row"),
+            Axis::Col => write!(f, "Column")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Dimensions {
     num_cols: usize,
     num_rows: usize,
@@ -17,9 +53,17 @@ impl Dimensions {
     pub fn new(num_rows: usize, num_cols: usize) -> Self {
         Dimensions { num_cols, num_rows }
     }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CellState {
     Full,
     Empty,
@@ -27,6 +71,72 @@ pub enum CellState {
     Invalid
 }
 
+impl CellState {
+    /// Whether the cell is known to be either full or empty.
+    pub fn is_determined(&self) -> bool {
+        matches!(self, CellState::Full | CellState::Empty)
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        *self == CellState::Unknown
+    }
+
+    pub fn is_full(&self) -> bool {
+        *self == CellState::Full
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == CellState::Empty
+    }
+
+    /// Two-character ASCII representation of the cell, for terminals where the block glyphs
+    /// used by `Display` render at different widths.
+    pub fn to_ascii(&self) -> &'static str {
+        match self {
+            CellState::Full => "[]",
+            CellState::Empty => "  ",
+            CellState::Unknown => "..",
+            CellState::Invalid => "xx"
+        }
+    }
+
+    /// `Some(true)` for full, `Some(false)` for empty and `None` for any other state.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            CellState::Full => Some(true),
+            CellState::Empty => Some(false),
+            _ => None
+        }
+    }
+}
+
+/// The encoding used by the JSON outputs: `1` for full, `0` for empty, `-1` for unknown and
+/// `-2` for invalid.
+impl From<CellState> for i8 {
+    fn from(cell: CellState) -> i8 {
+        match cell {
+            CellState::Full => 1,
+            CellState::Empty => 0,
+            CellState::Unknown => -1,
+            CellState::Invalid => -2
+        }
+    }
+}
+
+impl TryFrom<i8> for CellState {
+    type Error = SolverError;
+
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(CellState::Full),
+            0 => Ok(CellState::Empty),
+            -1 => Ok(CellState::Unknown),
+            -2 => Ok(CellState::Invalid),
+            _ => Err(SolverError::InvalidCellValue(value))
+        }
+    }
+}
+
 impl fmt::Display for CellState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -64,547 +174,4615 @@ impl BitOr for CellState {
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Constraint {
-    values: Vec<usize>
+/// Size of a single block in a clue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged, try_from = "RawClueValue"))]
+pub enum ClueValue {
+    Exact(usize),
+    /// A block of any size between the two bounds, inclusive. Build it with `ClueValue::range`,
+    /// which rejects empty ranges and ranges starting at 0.
+    Range(usize, usize)
 }
 
-impl Constraint {
-    pub fn new(values: Vec<usize>) -> Self {
-        Constraint { values }
-    }
-
-    fn filter(&self, candidates: &[Line]) -> Vec<Line> {
-        return candidates
-            .iter()
-            .filter(|line| line.to_constraint() == *self)
-            .cloned()
-            .collect::<Vec<Line>>();
-    }
+/// A `ClueValue` as read by serde, before the range is checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawClueValue {
+    Exact(usize),
+    Range(usize, usize)
 }
 
-pub struct Constraints {
-    cols: Vec<Constraint>,
-    rows: Vec<Constraint>
-}
+#[cfg(feature = "serde")]
+impl TryFrom<RawClueValue> for ClueValue {
+    type Error = SolverError;
 
-impl Constraints {
-    pub fn new(cols: Vec<Constraint>, rows: Vec<Constraint>) -> Self {
-        Constraints { cols, rows }
+    fn try_from(value: RawClueValue) -> Result<Self, Self::Error> {
+        match value {
+            RawClueValue::Exact(value) => Ok(ClueValue::Exact(value)),
+            RawClueValue::Range(min, max) => ClueValue::range(min, max)
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Line {
-    cells: Vec<CellState>
-}
-
-impl Line {
-    fn empty(length: usize) -> Self {
-        Line {
-            cells: vec![CellState::Empty; length]
+impl ClueValue {
+    /// A block of `min` to `max` cells. Fails unless `1 <= min <= max`.
+    pub fn range(min: usize, max: usize) -> Result<Self, SolverError> {
+        if min == 0 || min > max {
+            return Err(SolverError::InvalidClueRange { min, max })
         }
+        Ok(ClueValue::Range(min, max))
     }
-    
-    fn unknown(length: usize) -> Self {
-        Line {
-            cells: vec![CellState::Unknown; length]
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            ClueValue::Exact(_) => true,
+            ClueValue::Range(min, max) => 1 <= min && min <= max
         }
     }
 
-    fn new(cells: Vec<CellState>) -> Self {
-        Line { cells }
+    pub fn min(&self) -> usize {
+        match *self {
+            ClueValue::Exact(value) => value,
+            ClueValue::Range(min, _) => min
+        }
     }
 
-    fn to_constraint(&self) -> Constraint {
-        let values = self.cells
-            .iter()
-            .chunk_by(|&cell| *cell)
-            .into_iter()
-            .filter_map(|(key, group)| {
-                if key == CellState::Full {
-                    Some(group.count())
-                } else {
-                    None
-                }
-            })
-            .collect();
+    pub fn max(&self) -> usize {
+        match *self {
+            ClueValue::Exact(value) => value,
+            ClueValue::Range(_, max) => max
+        }
+    }
 
-        Constraint::new(values)
+    /// Whether a block of `size` cells satisfies the clue value.
+    pub fn covers(&self, size: usize) -> bool {
+        self.min() <= size && size <= self.max()
     }
+}
 
-    fn equivalient(&self, rhs: &Self) -> bool {
-        zip(&self.cells, &rhs.cells)
-        .all(|(first, second)| {
-            match (first, second) {
-                (CellState::Unknown, _) | (_, CellState::Unknown) => true,
-                _ => first == second
-            }
-        })
+impl From<usize> for ClueValue {
+    fn from(value: usize) -> Self {
+        ClueValue::Exact(value)
     }
+}
 
-    fn filter(&self, candidates: &[Self]) -> Vec<Self> {
-        candidates
-            .iter()
-            .filter(|line| self.equivalient(line))
-            .cloned()
-            .collect::<Vec<Line>>()
+/// `2..=4` is a block of 2, 3 or 4 cells. Fails like `ClueValue::range`.
+impl TryFrom<RangeInclusive<usize>> for ClueValue {
+    type Error = SolverError;
+
+    fn try_from(range: RangeInclusive<usize>) -> Result<Self, Self::Error> {
+        ClueValue::range(*range.start(), *range.end())
     }
+}
 
-    fn sum(lines: &[Self]) -> Option<Self> {
-        if lines.is_empty() {
-            return None
+impl fmt::Display for ClueValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClueValue::Exact(value) => write!(f, "{}", value),
+            ClueValue::Range(min, max) => write!(f, "{}-{}", min, max)
         }
+    }
+}
 
-        // TODO: Re-write this using function chaining
-        let mut new_line = lines[0].clone();
-        for line in &lines[1..] {
-            new_line = &new_line & line
-        }
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Constraint {
+    values: Vec<ClueValue>
+}
 
-        return Some(new_line)
+impl Constraint {
+    pub fn new(values: Vec<usize>) -> Self {
+        Constraint::with_clues(values.into_iter().map(ClueValue::Exact).collect())
     }
 
-    fn generate_combinations(blocks: &[Vec<CellState>], free_empty_spaces: usize) -> Vec<Self> {
-        // NOTE: Claude's translation of python code
-        let n_blocks = blocks.len();
-        let n_positions = n_blocks + 1;
-        
-        let mut results = Vec::new();
-        
-        // Generate all combinations of indices
-        let total_range = free_empty_spaces + n_positions - 1;
-        
-        for indices in (0..total_range).combinations(n_positions - 1) {
-            // Convert combination indices to counts per position
-            let mut counts = Vec::with_capacity(n_positions);
-            let mut prev = -1_i32;
-            
-            for &idx in &indices {
-                counts.push((idx as i32 - prev - 1) as usize);
-                prev = idx as i32;
-            }
-            counts.push((total_range as i32 - prev - 1) as usize);
-            
-            // Build the result list
-            let mut result = Vec::new();
-            
-            for (i, block) in blocks.iter().enumerate() {
-                // Add empty spaces before this block
-                for _ in 0..counts[i] {
-                    result.push(vec![CellState::Empty]);
-                }
-                // Add the block
-                result.push(block.clone());
-            }
-            
-            // Add trailing empty spaces
-            for _ in 0..counts[n_blocks] {
-                result.push(vec![CellState::Empty]);
-            }
+    /// Builds a constraint that may contain ranged block sizes, see `try_with_clues` for
+    /// values that haven't been checked yet.
+    pub fn with_clues(values: Vec<ClueValue>) -> Self {
+        debug_assert!(values.iter().all(ClueValue::is_valid), "ranges need 1 <= min <= max");
+        Constraint { values }
+    }
 
-            let line = Line::new(result
-                .into_iter()
-                .flatten()
-                .collect::<Vec<CellState>>());
-            
-            results.push(line);
+    /// Like `with_clues`, but fails if a range is empty or starts at 0, e.g. one built directly
+    /// as `ClueValue::Range(3, 1)`.
+    pub fn try_with_clues(values: Vec<ClueValue>) -> Result<Self, SolverError> {
+        if let Some(&ClueValue::Range(min, max)) = values.iter().find(|value| !value.is_valid()) {
+            return Err(SolverError::InvalidClueRange { min, max })
         }
-        
-        results
+        Ok(Constraint { values })
+    }
+
+    /// Builds a constraint from a clue given as block positions, e.g. by formats that list where
+    /// each block starts. Only the lengths make up the clue, check the positions with
+    /// `validate_positions` first.
+    pub fn from_positions(starts: &[usize], lengths: &[usize]) -> Self {
+        debug_assert_eq!(starts.len(), lengths.len(), "one start per block length");
+        Constraint::new(lengths.to_vec())
     }
 
-    fn generate_initial_candidates(length: usize, constraint: &Constraint) -> Vec<Self> {
-        if constraint.values.len() == 0 {
-            return vec![Line::empty(length)]
+    /// Checks that blocks starting at `starts` with the matching `lengths` fit in a line of
+    /// `length` cells: each block has to start at least one empty cell after the previous one
+    /// ends, and the last one has to end within the line.
+    pub fn validate_positions(starts: &[usize], lengths: &[usize], length: usize) -> Result<(), SolverError> {
+        if starts.len() != lengths.len() {
+            return Err(SolverError::PositionCountMismatch { starts: starts.len(), lengths: lengths.len() })
+        }
+
+        let mut next_free = 0;
+        for (block, (&start, &block_length)) in zip(starts, lengths).enumerate() {
+            if start < next_free {
+                return Err(SolverError::BlockOverlap { block })
+            }
+            if start + block_length > length {
+                return Err(SolverError::BlockOutOfRange { block, length })
+            }
+            next_free = start + block_length + 1;
         }
+        Ok(())
+    }
 
-        let mut blocks = constraint.values
+    /// Number of distinct lines of `length` cells that match the constraint. Saturates at
+    /// `usize::MAX`.
+    pub fn num_arrangements(&self, length: usize) -> usize {
+        self.expansions()
             .iter()
-            .map(|value| {
-                let mut block = vec![CellState::Full; *value];
-                block.push(CellState::Empty);
-                block
-            })
-            .collect::<Vec<Vec<CellState>>>();
+            .map(|values| Constraint::num_exact_arrangements(values, length))
+            .fold(0, usize::saturating_add)
+    }
 
-        let last_idx = blocks.len() - 1;
-        let last_item_idx = blocks[last_idx].len() - 1;
-        blocks[last_idx] = blocks[last_idx][..last_item_idx].to_vec();
+    /// Smallest and largest number of full cells the constraint allows.
+    fn total(&self) -> (usize, usize) {
+        let min = self.values.iter().map(ClueValue::min).sum();
+        let max = self.values.iter().map(ClueValue::max).sum();
+        (min, max)
+    }
 
-        let block_occupied_spaces: usize = blocks
-            .iter()
-            .map(|block| block.len())
-            .sum();
-        let free_empty_spaces = length - block_occupied_spaces;
+    /// Whether the blocks, with a single empty cell between each, fit in a line of `length` cells.
+    /// Ranged blocks are counted at their smallest size.
+    pub fn fits(&self, length: usize) -> bool {
+        self.min_length() <= length
+    }
 
-        Line::generate_combinations(&blocks, free_empty_spaces)
+    /// The shortest line the blocks fit in: their smallest sizes plus a single empty cell
+    /// between each.
+    pub fn min_length(&self) -> usize {
+        let gaps = self.values.len().saturating_sub(1);
+        self.values.iter().map(ClueValue::min).sum::<usize>() + gaps
     }
 
-}
+    /// The largest size any block can have, `0` for a line without blocks.
+    pub fn max_block(&self) -> usize {
+        self.values.iter().map(ClueValue::max).max().unwrap_or(0)
+    }
 
+    /// Whether the block sizes `blocks`, in order, satisfy the constraint.
+    fn covers(&self, blocks: &[usize]) -> bool {
+        self.values.len() == blocks.len()
+            && zip(&self.values, blocks).all(|(value, &size)| value.covers(size))
+    }
 
-impl fmt::Display for &Line {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.cells.iter().join(""))
+    /// Every sequence of exact block sizes the constraint allows. Exact constraints have exactly
+    /// one.
+    fn expansions(&self) -> Vec<Vec<usize>> {
+        if self.values.is_empty() {
+            return vec![vec![]]
+        }
+
+        self.values
+            .iter()
+            .map(|value| value.min()..=value.max())
+            .multi_cartesian_product()
+            .collect()
     }
-}
 
-impl BitAnd for &Line {
-    type Output = Line;
+    fn num_exact_arrangements(values: &[usize], length: usize) -> usize {
+        let num_blocks = values.len();
+        let occupied = values.iter().sum::<usize>() + num_blocks.saturating_sub(1);
+        if occupied > length {
+            return 0
+        }
 
-    fn bitand(self, rhs: Self) -> Self::Output {
-        let cells = zip(&self.cells, &rhs.cells)
-        .map(|(&first, &second)| {
-            first & second
-        })
-        .collect::<Vec<CellState>>();
-        Line::new(cells)
+        // Choose where the blocks go among the free cells: (free + blocks) choose blocks
+        let free = length - occupied;
+        let mut count: u128 = 1;
+        for k in 1..=num_blocks {
+            count = count * (free + k) as u128 / k as u128;
+            if count > usize::MAX as u128 {
+                return usize::MAX
+            }
+        }
+        count as usize
     }
-}
 
-impl BitOr for &Line {
-    type Output = Line;
+    /// The same blocks in the opposite order.
+    fn reversed(&self) -> Constraint {
+        Constraint::with_clues(self.values.iter().rev().copied().collect())
+    }
 
-    fn bitor(self, rhs: Self) -> Self::Output {
-        let cells = zip(&self.cells, &rhs.cells)
-        .map(|(&first, &second)| {
-            first | second
-        })
-        .collect::<Vec<CellState>>();
-        Line::new(cells)
+    #[allow(dead_code)]
+    fn filter(&self, candidates: &[Line]) -> Vec<Line> {
+        candidates
+            .iter()
+            .filter(|line| line.to_constraint() == *self)
+            .cloned()
+            .collect::<Vec<Line>>()
     }
 }
- 
-pub struct Board {
-    dimensions: Dimensions,
-    cells: Vec<CellState>,
-    row_constraints: Vec<Constraint>,
-    col_constraints: Vec<Constraint>,
-    row_candidates: Vec<Vec<Line>>,
-    col_candidates: Vec<Vec<Line>>
+
+/// A way of flipping the board that maps a puzzle's clues onto themselves, see
+/// `Constraints::symmetries`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symmetry {
+    /// Mirrored left to right
+    LeftRight,
+    /// Mirrored top to bottom
+    TopBottom,
+    /// Turned by 180 degrees
+    HalfTurn
 }
 
-impl Board {
-    pub fn new(constraints: Constraints, dimensions: Dimensions) -> Self {
-        let length = dimensions.num_cols;
-        
-        let row_candidates = constraints.rows
-            .iter()
-            .map(|constraint| Line::generate_initial_candidates(length, constraint))
-            .collect::<Vec<Vec<Line>>>();
-        let col_candidates = constraints.cols
-            .iter()
-            .map(|constraint| Line::generate_initial_candidates(length, constraint))
-            .collect::<Vec<Vec<Line>>>();
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Constraints {
+    cols: Vec<Constraint>,
+    rows: Vec<Constraint>
+}
 
-        let cells = vec![CellState::Unknown; dimensions.num_cols * dimensions.num_rows];
+impl Constraints {
+    pub fn new(cols: Vec<Constraint>, rows: Vec<Constraint>) -> Self {
+        Constraints { cols, rows }
+    }
 
-        Board { 
-            dimensions: dimensions, 
-            cells: cells,
-            row_constraints: constraints.rows, 
-            col_constraints: constraints.cols, 
-            row_candidates: row_candidates, 
-            col_candidates: col_candidates
-        }
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
     }
 
-    // TODO: Enable branching to enable guessing
-    pub fn solve(&mut self) {
-        let mut prev_num_unknown = self.num_unknown();
-        
-        let mut solve_rows = true;
-        let mut state_string = String::new();
-        while !self.is_solved() {
-            self.update_candidates(solve_rows);
-            self.update_cells(solve_rows);
+    pub fn num_cols(&self) -> usize {
+        self.cols.len()
+    }
 
-            if self.num_unknown() == prev_num_unknown {
-                // Board has multiple solutions
-                break
-            }
+    /// Whether the clues have at least one solution on a board of `dimensions`. Only the answer
+    /// is kept, the search stops at the first solution.
+    pub fn is_solvable(&self, dimensions: &Dimensions) -> bool {
+        self.num_solutions_up_to(dimensions, 1) == 1
+    }
 
-            solve_rows = !solve_rows;
-            prev_num_unknown = self.num_unknown();
+    /// Whether the clues have exactly one solution on a board of `dimensions`. The search stops
+    /// as soon as a second solution turns up.
+    pub fn is_unique(&self, dimensions: &Dimensions) -> bool {
+        self.num_solutions_up_to(dimensions, 2) == 1
+    }
 
-            // println!("{}", self.to_string());
+    /// Counts solutions until `limit` are found. Clues that can't fit the board, whose totals
+    /// disagree or with a line too short for its clue are rejected before any candidates are
+    /// generated.
+    fn num_solutions_up_to(&self, dimensions: &Dimensions, limit: usize) -> usize {
+        let lines_fit = self.rows.iter().all(|constraint| constraint.fits(dimensions.num_cols))
+            && self.cols.iter().all(|constraint| constraint.fits(dimensions.num_rows));
+        if !self.matches(dimensions) || self.check_totals().is_err() || !lines_fit {
+            return 0
         }
-    }
 
-    fn to_line(&self, idx: usize, is_row: bool) -> Line {
-        if is_row {
-            let start = idx * self.dimensions.num_cols;
-            let end = start + self.dimensions.num_cols;
-            let cells = self.cells[start..end].to_vec();
-            return Line::new(cells)
-        } else {
-            let cells = (0..self.dimensions.num_rows)
-                .map(|row_idx| self.cells[row_idx * self.dimensions.num_cols + idx])
-                .collect();
-            Line::new(cells)
+        let mut board = Board::new(self.clone(), *dimensions);
+        if let SolveOutcome::Contradiction { .. } = board.solve() {
+            return 0
         }
+        let mut solutions = Vec::new();
+        board.search_solutions(limit, &mut solutions);
+        solutions.len()
     }
 
-    fn or_line(&mut self, idx: usize, is_row: bool, line: &Line) {
-        if is_row {
-            let start = idx * self.dimensions.num_cols;
-            let end = start + self.dimensions.num_cols;
-            self.cells[start..end].copy_from_slice(&line.cells[..]);
+    /// The symmetries the clues have. If the clues map onto themselves, so do their solutions:
+    /// the mirror image of a solution is a solution as well.
+    pub fn symmetries(&self) -> Vec<Symmetry> {
+        // Line `idx` has to carry the clue of line `num - 1 - idx`, either as is or reversed
+        let mirrored = |constraints: &[Constraint], reverse: bool| {
+            zip(constraints, constraints.iter().rev()).all(|(constraint, opposite)| {
+                if reverse { *constraint == opposite.reversed() } else { constraint == opposite }
+            })
+        };
+        let palindromes = |constraints: &[Constraint]| {
+            constraints.iter().all(|constraint| *constraint == constraint.reversed())
+        };
+
+        let mut symmetries = Vec::new();
+        if palindromes(&self.rows) && mirrored(&self.cols, false) {
+            symmetries.push(Symmetry::LeftRight);
+        }
+        if palindromes(&self.cols) && mirrored(&self.rows, false) {
+            symmetries.push(Symmetry::TopBottom);
+        }
+        if mirrored(&self.rows, true) && mirrored(&self.cols, true) {
+            symmetries.push(Symmetry::HalfTurn);
+        }
+        symmetries
+    }
+
+    /// Every full cell is counted once by its row clue and once by its column clue, so both
+    /// totals must agree for the puzzle to have a solution. Ranged clues only fail the check if
+    /// the possible totals don't overlap. The error reports the smallest totals.
+    pub fn check_totals(&self) -> Result<(), SolverError> {
+        Constraints::totals_match(&self.rows, &self.cols)
+    }
+
+    fn totals_match(rows: &[Constraint], cols: &[Constraint]) -> Result<(), SolverError> {
+        let total = |constraints: &[Constraint]| {
+            constraints
+                .iter()
+                .map(Constraint::total)
+                .fold((0, 0), |(min, max), (line_min, line_max)| (min + line_min, max + line_max))
+        };
+        let (row_min, row_max) = total(rows);
+        let (col_min, col_max) = total(cols);
+
+        if row_min > col_max || col_min > row_max {
+            return Err(SolverError::ClueTotalMismatch { row_total: row_min, col_total: col_min })
+        }
+        Ok(())
+    }
+
+    /// Spells out every clue for reading aloud, rows first and then columns, numbered from 1:
+    /// `"Row 1: 2 3 / Row 2: (empty) / Column 1: 1 / ..."`.
+    pub fn to_clue_text(&self) -> String {
+        let describe = |label: &str, constraints: &[Constraint]| {
+            constraints
+                .iter()
+                .enumerate()
+                .map(|(idx, constraint)| {
+                    let values = if constraint.values.is_empty() {
+                        "(empty)".to_string()
+                    } else {
+                        constraint.values.iter().join(" ")
+                    };
+                    format!("{} {}: {}", label, idx + 1, values)
+                })
+                .collect::<Vec<String>>()
+        };
+
+        describe("Row", &self.rows)
+            .into_iter()
+            .chain(describe("Column", &self.cols))
+            .join(" / ")
+    }
+
+    /// The board size the clues describe: one row per row clue and one column per column clue.
+    /// Fails if a row clue needs more cells than there are columns or a column clue more than
+    /// there are rows.
+    pub fn infer_dimensions(&self) -> Result<Dimensions, SolverError> {
+        let dimensions = Dimensions::new(self.num_rows(), self.num_cols());
+
+        let too_long = |constraints: &[Constraint], length: usize| {
+            constraints.iter().position(|constraint| constraint.min_length() > length)
+        };
+        if let Some(index) = too_long(&self.rows, dimensions.num_cols) {
+            return Err(SolverError::Infeasible { index, axis: Axis::Row })
+        }
+        if let Some(index) = too_long(&self.cols, dimensions.num_rows) {
+            return Err(SolverError::Infeasible { index, axis: Axis::Col })
+        }
+        Ok(dimensions)
+    }
+
+    /// Whether there is exactly one clue per row and per column of `dimensions`.
+    pub fn matches(&self, dimensions: &Dimensions) -> bool {
+        self.num_rows() == dimensions.num_rows && self.num_cols() == dimensions.num_cols
+    }
+
+    /// Same as `matches`, but reports the mismatch as an error, with the number of row and
+    /// column clues as the actual size.
+    pub fn check_dimensions(&self, dimensions: &Dimensions) -> Result<(), SolverError> {
+        if !self.matches(dimensions) {
+            return Err(SolverError::GridSizeMismatch {
+                expected: (dimensions.num_rows, dimensions.num_cols),
+                actual: (self.num_rows(), self.num_cols())
+            })
+        }
+        Ok(())
+    }
+
+    /// Estimates the candidate lines `Board::new` would generate for these clues, returned as
+    /// `(candidates, approximate bytes)`.
+    pub fn memory_estimate(&self, dimensions: &Dimensions) -> (usize, usize) {
+        let row_candidates = self.rows
+            .iter()
+            .map(|constraint| constraint.num_arrangements(dimensions.num_cols))
+            .fold(0, usize::saturating_add);
+        let col_candidates = self.cols
+            .iter()
+            .map(|constraint| constraint.num_arrangements(dimensions.num_rows))
+            .fold(0, usize::saturating_add);
+
+        let line_bytes = |length: usize| size_of::<Line>() + length * size_of::<CellState>();
+        let bytes = row_candidates.saturating_mul(line_bytes(dimensions.num_cols))
+            .saturating_add(col_candidates.saturating_mul(line_bytes(dimensions.num_rows)));
+
+        (row_candidates.saturating_add(col_candidates), bytes)
+    }
+
+    /// Groups the indices of rows that share an identical clue. Only groups with at least two
+    /// rows are returned, ordered by their first row.
+    pub fn duplicate_rows(&self) -> Vec<Vec<usize>> {
+        Constraints::duplicate_groups(&self.rows)
+    }
+
+    /// Groups the indices of columns that share an identical clue. Only groups with at least two
+    /// columns are returned, ordered by their first column.
+    pub fn duplicate_cols(&self) -> Vec<Vec<usize>> {
+        Constraints::duplicate_groups(&self.cols)
+    }
+
+    fn duplicate_groups(constraints: &[Constraint]) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (idx, constraint) in constraints.iter().enumerate() {
+            match groups.iter_mut().find(|group| constraints[group[0]] == *constraint) {
+                Some(group) => group.push(idx),
+                None => groups.push(vec![idx])
+            }
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+}
+
+/// Reads the row clues from `rows_csv` and the column clues from `cols_csv`, one line's blocks
+/// per CSV line, as described in `formats::csv::parse_rows`.
+pub fn parse_csv(rows_csv: &str, cols_csv: &str) -> Result<Constraints, ParseIntError> {
+    let rows = crate::formats::csv::parse_rows(rows_csv)?;
+    let cols = crate::formats::csv::parse_cols(cols_csv)?;
+    Ok(Constraints::new(cols, rows))
+}
+
+/// Reads a grid of the integers `i8::from(CellState)` gives: `1` for full, `0` for empty and
+/// `-1` for unknown. Any other value becomes `Invalid`. Note that the `solution` grids of the
+/// benchmark data use `2` and `1` instead.
+pub fn grid_from_ints(grid: &[Vec<i32>]) -> Vec<Vec<CellState>> {
+    grid.iter()
+        .map(|row| {
+            row.iter()
+                .map(|&value| {
+                    i8::try_from(value)
+                        .ok()
+                        .and_then(|value| CellState::try_from(value).ok())
+                        .unwrap_or(CellState::Invalid)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Inverse of `grid_from_ints`. `Invalid` cells become `-2`.
+pub fn grid_to_ints(grid: &[Vec<CellState>]) -> Vec<Vec<i32>> {
+    grid.iter()
+        .map(|row| row.iter().map(|&cell| i8::from(cell) as i32).collect())
+        .collect()
+}
+
+/// A puzzle's clues along with the size of the board they're for. The size follows from the
+/// number of clues, so the two always match. With the `serde` feature it (de)serializes as its
+/// `Constraints`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(from = "Constraints", into = "Constraints"))]
+pub struct Puzzle {
+    constraints: Constraints,
+    dimensions: Dimensions
+}
+
+impl Puzzle {
+    pub fn new(constraints: Constraints) -> Self {
+        let dimensions = Dimensions::new(constraints.num_rows(), constraints.num_cols());
+        Puzzle { constraints, dimensions }
+    }
+
+    /// Builds a puzzle from plain block sizes, the rows from top to bottom and the columns from
+    /// left to right.
+    pub fn from_clues(rows: &[Vec<usize>], cols: &[Vec<usize>]) -> Self {
+        let to_constraints = |clues: &[Vec<usize>]| {
+            clues
+                .iter()
+                .map(|values| Constraint::new(values.clone()))
+                .collect::<Vec<Constraint>>()
+        };
+
+        Puzzle::new(Constraints::new(to_constraints(cols), to_constraints(rows)))
+    }
+
+    pub fn constraints(&self) -> &Constraints {
+        &self.constraints
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// A fresh, unsolved board for the puzzle.
+    pub fn board(&self) -> Board {
+        Board::new(self.constraints.clone(), self.dimensions)
+    }
+
+    pub fn solve(&self) -> SolveResult {
+        let mut board = self.board();
+        let outcome = board.solve();
+        SolveResult { outcome, grid: board.grid(), stats: board.stats().clone() }
+    }
+
+    /// Like `solve`, but guesses cells if propagation stalls, see `Board::solve_any`.
+    /// `stats.used_search` tells whether guessing was needed. If the clues have no solution
+    /// the result is that of propagation alone.
+    pub fn solve_any(&self) -> SolveResult {
+        let mut board = self.board();
+        let outcome = match board.solve_any() {
+            Some(grid) => {
+                board.apply_solution(&grid).expect("solve_any only returns grids matching the clues");
+                SolveOutcome::Solved
+            },
+            None => board.solve()
+        };
+        SolveResult { outcome, grid: board.grid(), stats: board.stats().clone() }
+    }
+}
+
+impl From<Constraints> for Puzzle {
+    fn from(constraints: Constraints) -> Self {
+        Puzzle::new(constraints)
+    }
+}
+
+impl From<Puzzle> for Constraints {
+    fn from(puzzle: Puzzle) -> Self {
+        puzzle.constraints
+    }
+}
+
+/// Longest line whose cells `Line` keeps as bitmasks instead of a `Vec<CellState>`.
+pub const MAX_BITMASK_LINE: usize = 128;
+
+/// How a `Line` stores its cells. Every line of up to `MAX_BITMASK_LINE` cells without an
+/// `Invalid` cell is kept as `Bits`, which turns `&`, `|` and the agreement checks into a few
+/// integer operations and needs no allocation. Longer lines and lines with conflicts fall back
+/// to `Cells`.
+#[derive(Debug, Clone)]
+enum LineCells {
+    /// Bit `i` of `known` is set if cell `i` is full or empty, and bit `i` of `filled` if it's full
+    Bits { filled: u128, known: u128, len: usize },
+    Cells(Vec<CellState>)
+}
+
+/// Mask with the lowest `len` bits set.
+fn low_bits(len: usize) -> u128 {
+    if len >= u128::BITS as usize { u128::MAX } else { (1 << len) - 1 }
+}
+
+/// Advances a xorshift generator and returns its new state, `state` must never be zero.
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[derive(Debug, Clone)]
+pub struct Line {
+    cells: LineCells
+}
+
+impl Line {
+    fn empty(length: usize) -> Self {
+        Line::new(vec![CellState::Empty; length])
+    }
+    
+    fn full(length: usize) -> Self {
+        Line::new(vec![CellState::Full; length])
+    }
+    
+    fn unknown(length: usize) -> Self {
+        Line::new(vec![CellState::Unknown; length])
+    }
+
+    pub fn new(cells: Vec<CellState>) -> Self {
+        if cells.len() > MAX_BITMASK_LINE || cells.contains(&CellState::Invalid) {
+            return Line::with_cells_backend(cells)
+        }
+
+        let mut filled = 0;
+        let mut known = 0;
+        for (idx, cell) in cells.iter().enumerate() {
+            if cell.is_determined() {
+                known |= 1 << idx;
+            }
+            if cell.is_full() {
+                filled |= 1 << idx;
+            }
+        }
+        Line { cells: LineCells::Bits { filled, known, len: cells.len() } }
+    }
+
+    /// Keeps the cells in a `Vec` even if the line fits in bitmasks. Behaves exactly like a line
+    /// built with `new`, only slower, which makes it useful for comparing the two backends.
+    pub fn with_cells_backend(cells: Vec<CellState>) -> Self {
+        Line { cells: LineCells::Cells(cells) }
+    }
+
+    /// Copies the cells out, whichever way the line stores them.
+    pub fn cells(&self) -> Vec<CellState> {
+        match &self.cells {
+            LineCells::Bits { len, .. } => (0..*len).map(|idx| self.cell(idx)).collect(),
+            LineCells::Cells(cells) => cells.clone()
+        }
+    }
+
+    pub fn cell(&self, idx: usize) -> CellState {
+        match &self.cells {
+            LineCells::Bits { filled, known, .. } => match (known >> idx & 1, filled >> idx & 1) {
+                (1, 1) => CellState::Full,
+                (1, _) => CellState::Empty,
+                _ => CellState::Unknown
+            },
+            LineCells::Cells(cells) => cells[idx]
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.cells {
+            LineCells::Bits { len, .. } => *len,
+            LineCells::Cells(cells) => cells.len()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `(filled, known)` if the line is kept as bitmasks.
+    fn bits(&self) -> Option<(u128, u128)> {
+        match self.cells {
+            LineCells::Bits { filled, known, .. } => Some((filled, known)),
+            LineCells::Cells(_) => None
+        }
+    }
+
+    /// Cells forced by `constraint` alone in an otherwise unknown line of `length` cells, i.e. the
+    /// overlap of all its arrangements. Returns `None` if the clue doesn't fit.
+    pub fn overlap(length: usize, constraint: &Constraint) -> Option<Self> {
+        Line::forced_given(constraint, &Line::unknown(length))
+    }
+
+    /// Deduces what `constraint` forces in a partially known line. Every arrangement agreeing
+    /// with the known cells of `given` is considered; cells on which they all agree are set and
+    /// the rest are `Unknown`. Returns `None` if no arrangement agrees with `given`.
+    pub fn forced_given(constraint: &Constraint, given: &Line) -> Option<Self> {
+        Line::streamed_sum(given.len(), constraint, given, 1)
+    }
+
+    /// Every line of `length` cells matching `constraint`, in reading order: sorted by where each
+    /// block starts, comparing the first block first. Candidate `[0]` is therefore the all-left
+    /// packing and the last candidate the all-right packing.
+    pub fn enumerate_lines(length: usize, constraint: &Constraint) -> Vec<Self> {
+        let mut lines = Line::arrangements(length, constraint, 1).collect::<Vec<Line>>();
+        lines.sort_by_cached_key(Line::block_starts);
+        lines
+    }
+
+    fn block_starts(&self) -> Vec<usize> {
+        let cells = self.cells();
+        (0..cells.len())
+            .filter(|&idx| cells[idx].is_full() && (idx == 0 || !cells[idx - 1].is_full()))
+            .collect()
+    }
+
+    pub(crate) fn to_constraint(&self) -> Constraint {
+        let values = self.cells()
+            .into_iter()
+            .chunk_by(|&cell| cell)
+            .into_iter()
+            .filter_map(|(key, group)| {
+                if key == CellState::Full {
+                    Some(group.count())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Constraint::new(values)
+    }
+
+    /// Returns a copy of the line with every `Unknown` cell replaced by `state`.
+    pub fn fill_unknown(&self, state: CellState) -> Self {
+        Line::new(self.cells()
+            .into_iter()
+            .map(|cell| if cell.is_unknown() { state } else { cell })
+            .collect())
+    }
+
+    /// Whether every cell is known, i.e. neither `Unknown` nor `Invalid`.
+    pub fn is_complete(&self) -> bool {
+        match &self.cells {
+            LineCells::Bits { known, len, .. } => *known == low_bits(*len),
+            LineCells::Cells(cells) => cells.iter().all(CellState::is_determined)
+        }
+    }
+
+    /// Renders the line one ASCII character per cell, in the notation `formats::art` reads: `#`
+    /// for full and `.` for empty cells, plus `?` for unknown and `x` for invalid ones.
+    pub fn to_compact_string(&self) -> String {
+        self.cells()
+            .iter()
+            .map(|cell| match cell {
+                CellState::Full => '#',
+                CellState::Empty => '.',
+                CellState::Unknown => '?',
+                CellState::Invalid => 'x'
+            })
+            .collect()
+    }
+
+    /// Whether the line is complete and its blocks match `constraint`.
+    pub fn is_valid_for(&self, constraint: &Constraint) -> bool {
+        let blocks = self.to_constraint()
+            .values
+            .iter()
+            .map(ClueValue::min)
+            .collect::<Vec<usize>>();
+        self.is_complete() && constraint.covers(&blocks)
+    }
+
+    /// Same as `is_valid_for`, but the blocks also have to be at least `min_gap` empty cells
+    /// apart, see `SolveConfig::min_gap`.
+    fn is_valid_with_gap(&self, constraint: &Constraint, min_gap: usize) -> bool {
+        if !self.is_complete() || !self.is_valid_for(constraint) {
+            return false
+        }
+        if min_gap <= 1 {
+            // Blocks are always at least one cell apart
+            return true
+        }
+        let lengths = self.to_constraint().values;
+        zip(self.block_starts().windows(2), lengths)
+            .all(|(starts, length)| starts[1] - starts[0] - length.min() >= min_gap)
+    }
+
+    fn equivalient(&self, rhs: &Self) -> bool {
+        if let (Some((filled, known)), Some((rhs_filled, rhs_known))) = (self.bits(), rhs.bits()) {
+            return (filled ^ rhs_filled) & known & rhs_known == 0
+        }
+
+        zip(self.cells(), rhs.cells())
+        .all(|(first, second)| {
+            match (first, second) {
+                (CellState::Unknown, _) | (_, CellState::Unknown) => true,
+                _ => first == second
+            }
+        })
+    }
+
+    /// Whether any cell isn't `Unknown`.
+    fn has_known_cells(&self) -> bool {
+        match &self.cells {
+            LineCells::Bits { known, .. } => *known != 0,
+            LineCells::Cells(cells) => !cells.iter().all(CellState::is_unknown)
+        }
+    }
+
+    /// Whether the line has the same state as `current` wherever `current` isn't `Unknown`.
+    /// Unlike `equivalient`, an `Unknown` cell of this line doesn't match a known one.
+    fn agrees_with(&self, current: &Line) -> bool {
+        if let (Some((filled, known)), Some((current_filled, current_known))) = (self.bits(), current.bits()) {
+            return current_known & !known == 0 && (filled ^ current_filled) & current_known == 0
+        }
+
+        zip(self.cells(), current.cells()).all(|(cell, current)| current.is_unknown() || cell == current)
+    }
+
+    #[allow(dead_code)]
+    fn filter(&self, candidates: &[Self]) -> Vec<Self> {
+        candidates
+            .iter()
+            .filter(|line| self.equivalient(line))
+            .cloned()
+            .collect::<Vec<Line>>()
+    }
+
+    fn sum(lines: &[Self]) -> Option<Self> {
+        if lines.is_empty() {
+            return None
+        }
+
+        // Same as `&` over all lines, but without a new line per step
+        if let LineCells::Bits { len, .. } = lines[0].cells {
+            let bits = lines.iter().map(Line::bits).collect::<Option<Vec<(u128, u128)>>>();
+            if let Some(bits) = bits {
+                let (filled, known) = bits[1..].iter().fold(bits[0], |(filled, known), &(rhs_filled, rhs_known)| {
+                    let known = known & rhs_known & !(filled ^ rhs_filled);
+                    (filled & rhs_filled & known, known)
+                });
+                return Some(Line { cells: LineCells::Bits { filled, known, len } })
+            }
+        }
+
+        let mut new_line = lines[0].clone();
+        for line in &lines[1..] {
+            new_line = &new_line & line
+        }
+
+        Some(new_line)
+    }
+
+    /// Returns the solved line if the constraint leaves only one trivial arrangement, i.e. no
+    /// blocks at all or a single block spanning the whole line.
+    fn trivial(length: usize, constraint: &Constraint) -> Option<Self> {
+        match constraint.values[..] {
+            [] => Some(Line::empty(length)),
+            [ClueValue::Exact(value)] if value == length => Some(Line::full(length)),
+            _ => None
+        }
+    }
+
+    /// Lazily yields every way of spreading `free_empty_spaces` empty cells around the blocks.
+    fn generate_combinations(blocks: Vec<Vec<CellState>>, free_empty_spaces: usize) -> impl Iterator<Item = Self> {
+        // NOTE: Claude's translation of python code
+        let n_blocks = blocks.len();
+        let n_positions = n_blocks + 1;
+        
+        // Generate all combinations of indices
+        let total_range = free_empty_spaces + n_positions - 1;
+
+        // Lines that fit in the bitmasks get each block's full cells shifted into place instead
+        let length = free_empty_spaces + blocks.iter().map(Vec::len).sum::<usize>();
+        let block_masks = blocks
+            .iter()
+            .map(|block| {
+                block.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.is_full())
+                    .fold(0_u128, |mask, (pos, _)| mask | 1 << pos)
+            })
+            .collect::<Vec<u128>>();
+        
+        (0..total_range).combinations(n_positions - 1).map(move |indices| {
+            // Convert combination indices to counts per position
+            let mut counts = Vec::with_capacity(n_positions);
+            let mut prev = -1_i32;
+            
+            for &idx in &indices {
+                counts.push((idx as i32 - prev - 1) as usize);
+                prev = idx as i32;
+            }
+            counts.push((total_range as i32 - prev - 1) as usize);
+
+            if length <= MAX_BITMASK_LINE {
+                let mut filled = 0;
+                let mut pos = 0;
+                for (i, block) in blocks.iter().enumerate() {
+                    pos += counts[i];
+                    filled |= block_masks[i] << pos;
+                    pos += block.len();
+                }
+                return Line { cells: LineCells::Bits { filled, known: low_bits(length), len: length } }
+            }
+            
+            // Build the result list
+            let mut result = Vec::new();
+            
+            for (i, block) in blocks.iter().enumerate() {
+                // Add empty spaces before this block
+                for _ in 0..counts[i] {
+                    result.push(vec![CellState::Empty]);
+                }
+                // Add the block
+                result.push(block.clone());
+            }
+            
+            // Add trailing empty spaces
+            for _ in 0..counts[n_blocks] {
+                result.push(vec![CellState::Empty]);
+            }
+
+            Line::new(result
+                .into_iter()
+                .flatten()
+                .collect::<Vec<CellState>>())
+        })
+    }
+
+    /// Lazily yields every line of `length` cells matching `constraint`, with at least `min_gap`
+    /// empty cells between consecutive blocks. Ranged clues are expanded to every combination of
+    /// exact block sizes.
+    fn arrangements(length: usize, constraint: &Constraint, min_gap: usize) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(constraint
+            .expansions()
+            .into_iter()
+            .flat_map(move |values| Line::exact_arrangements(length, &values, min_gap)))
+    }
+
+    fn exact_arrangements(length: usize, values: &[usize], min_gap: usize) -> Box<dyn Iterator<Item = Self>> {
+        if values.is_empty() {
+            return Box::new(std::iter::once(Line::empty(length)))
+        }
+
+        let min_gap = min_gap.max(1);
+        let gaps = (values.len() - 1) * min_gap;
+        if values.iter().sum::<usize>() + gaps > length {
+            return Box::new(std::iter::empty())
+        }
+
+        // Every block but the last carries its mandatory gap with it
+        let mut blocks = values
+            .iter()
+            .map(|value| {
+                let mut block = vec![CellState::Full; *value];
+                block.extend(std::iter::repeat_n(CellState::Empty, min_gap));
+                block
+            })
+            .collect::<Vec<Vec<CellState>>>();
+
+        let last_idx = blocks.len() - 1;
+        blocks[last_idx].truncate(values[last_idx]);
+
+        let block_occupied_spaces: usize = blocks
+            .iter()
+            .map(|block| block.len())
+            .sum();
+        let free_empty_spaces = length - block_occupied_spaces;
+
+        Box::new(Line::generate_combinations(blocks, free_empty_spaces))
+    }
+
+    fn generate_initial_candidates(length: usize, constraint: &Constraint, min_gap: usize) -> Vec<Self> {
+        Line::arrangements(length, constraint, min_gap).collect()
+    }
+
+    /// Same as summing the arrangements of `constraint` that are equivalent to `current`, but
+    /// each arrangement is dropped as soon as it has been folded in so only one line is kept
+    /// in memory at a time.
+    fn streamed_sum(length: usize, constraint: &Constraint, current: &Line, min_gap: usize) -> Option<Self> {
+        Line::arrangements(length, constraint, min_gap)
+            .filter(|line| line.equivalient(current))
+            .reduce(|sum, line| &sum & &line)
+    }
+
+}
+
+
+/// Lines are equal if their cells are, whichever way they're stored.
+impl PartialEq for Line {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.cells, &other.cells) {
+            (LineCells::Bits { .. }, LineCells::Bits { .. }) => self.len() == other.len() && self.bits() == other.bits(),
+            (LineCells::Cells(cells), LineCells::Cells(other_cells)) => cells == other_cells,
+            _ => self.cells() == other.cells()
+        }
+    }
+}
+
+impl Eq for Line {}
+
+/// Hashes the cells one by one, so equal lines hash alike whichever way they're stored.
+impl Hash for Line {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for idx in 0..self.len() {
+            self.cell(idx).hash(state);
+        }
+    }
+}
+
+impl fmt::Display for &Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.cells().iter().join(""))
+    }
+}
+
+impl BitAnd for &Line {
+    type Output = Line;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        if let (Some((filled, known)), Some((rhs_filled, rhs_known))) = (self.bits(), rhs.bits()) {
+            let known = known & rhs_known & !(filled ^ rhs_filled);
+            return Line { cells: LineCells::Bits { filled: filled & rhs_filled & known, known, len: self.len() } }
+        }
+
+        let cells = zip(self.cells(), rhs.cells())
+        .map(|(first, second)| {
+            first & second
+        })
+        .collect::<Vec<CellState>>();
+        Line::new(cells)
+    }
+}
+
+impl BitOr for &Line {
+    type Output = Line;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        if let (Some((filled, known)), Some((rhs_filled, rhs_known))) = (self.bits(), rhs.bits()) {
+            // Cells known to differ become `Invalid`, which only the `Vec` backend can hold
+            if (filled ^ rhs_filled) & known & rhs_known == 0 {
+                return Line { cells: LineCells::Bits { filled: filled | rhs_filled, known: known | rhs_known, len: self.len() } }
+            }
+        }
+
+        let cells = zip(self.cells(), rhs.cells())
+        .map(|(first, second)| {
+            first | second
+        })
+        .collect::<Vec<CellState>>();
+        Line::new(cells)
+    }
+}
+ 
+/// Counters collected while solving a board.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveStats {
+    /// Number of single-direction propagation passes
+    pub passes: usize,
+    /// Number of candidate lines generated when the board was built
+    pub candidates_generated: usize,
+    /// Number of guesses `Board::solve_any` had to take back
+    pub backtracks: usize,
+    /// Whether `Board::solve_any` had to guess because propagation alone stalled
+    pub used_search: bool,
+    /// Number of candidate lines compared against the grid while pruning
+    pub candidates_checked: usize,
+    /// `(row, col)` of every cell left `Invalid` by the last solve, i.e. where a row and a
+    /// column wrote conflicting states
+    pub invalid_cells: Vec<(usize, usize)>,
+    /// The lines that took the most work
+    pub hardest_line: HardestLine
+}
+
+/// The lines that took the most work to solve, each as `(index, axis, amount)`, see
+/// `SolveStats::hardest_line`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HardestLine {
+    /// The line with the most candidates when the board was built and how many it had. Ties go
+    /// to rows, then to the lower index. `None` in streaming mode, where no candidates are kept.
+    pub most_candidates: Option<(usize, Axis, usize)>,
+    /// The line that was fully determined last in the last solve and the number of passes that
+    /// took. Lines left undetermined aren't counted.
+    pub most_passes: Option<(usize, Axis, usize)>
+}
+
+/// The cells a single line's clue filled in, see `Board::fill_forced_then_stop`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deduction {
+    pub axis: Axis,
+    pub index: usize,
+    /// `(row, col)` of every cell that was filled in, in order along the line
+    pub cells: Vec<(usize, usize)>
+}
+
+/// How a call to `Board::solve` ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolveOutcome {
+    /// Every cell is determined
+    Solved,
+    /// Propagation stopped making progress with cells still unknown
+    Stalled,
+    /// No arrangement of the line's clue agrees with the grid, e.g. after `set_cell`
+    Contradiction { index: usize, axis: Axis }
+}
+
+/// What `Puzzle::solve` found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveResult {
+    pub outcome: SolveOutcome,
+    /// The grid after solving, one `Vec` per row
+    pub grid: Vec<Vec<CellState>>,
+    pub stats: SolveStats
+}
+
+/// Tuning knobs for `Board::solve`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveConfig {
+    /// The solver gives up once a full row + column cycle determines fewer cells than this.
+    /// Values below 1 are treated as 1.
+    pub min_progress_per_cycle: usize,
+    /// Don't store candidate lines, instead re-enumerate each line's arrangements on every pass.
+    /// Trades time for memory on wide lines with many arrangements.
+    pub streaming: bool,
+    /// When `Board::solve_any` guesses a cell, try the value most of the cell's remaining
+    /// candidates agree on first instead of always trying `Full` first.
+    pub weighted_guesses: bool,
+    /// When the clues have a symmetry (see `Constraints::symmetries`), `Board::solve_any` only
+    /// searches one of each pair of mirror image branches: once guessing a cell one way has
+    /// failed, the cell's mirror image can't be that way either.
+    pub symmetry_pruning: bool,
+    /// Fewest empty cells between two blocks of a line, for variants that need more than one.
+    /// Values below 1 are treated as 1. The board's own checks such as `Board::validate` follow
+    /// this, but `Constraint` knows nothing of it, so e.g. `Constraint::fits` still assumes a
+    /// single empty cell.
+    pub min_gap: usize,
+    /// Keep passing over the same direction while each pass determines at least
+    /// `min_progress_per_cycle` cells and only switch when it falls short, instead of strictly
+    /// alternating. Solving stops once both directions fall short in a row.
+    pub adaptive_direction: bool
+}
+
+impl Default for SolveConfig {
+    fn default() -> Self {
+        SolveConfig { 
+            min_progress_per_cycle: 1,
+            streaming: false,
+            weighted_guesses: true,
+            symmetry_pruning: false,
+            min_gap: 1,
+            adaptive_direction: false
+        }
+    }
+}
+
+/// Cumulative time spent filtering each line's candidates, see `Board::timing_report`.
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Default)]
+struct LineTimings {
+    rows: Vec<std::time::Duration>,
+    cols: Vec<std::time::Duration>
+}
+
+#[cfg(feature = "profiling")]
+impl LineTimings {
+    fn new(dimensions: Dimensions) -> Self {
+        LineTimings {
+            rows: vec![std::time::Duration::ZERO; dimensions.num_rows],
+            cols: vec![std::time::Duration::ZERO; dimensions.num_cols]
+        }
+    }
+}
+
+/// Time spent filtering one line's candidates, see `Board::timing_report`.
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineTiming {
+    pub axis: Axis,
+    pub index: usize,
+    pub time: std::time::Duration
+}
+
+/// Where the solve loop is between two passes, so it can be paused, see `SolverHandle`.
+#[derive(Debug, Clone)]
+struct PassState {
+    axis: Axis,
+    cycle_progress: usize,
+    stalled_passes: usize,
+    passes: usize,
+    // Lines that weren't fully determined after the last pass, `None` before the first pass
+    unresolved: Option<Vec<(usize, Axis)>>
+}
+
+impl Default for PassState {
+    fn default() -> Self {
+        PassState { axis: Axis::Row, cycle_progress: 0, stalled_passes: 0, passes: 0, unresolved: None }
+    }
+}
+
+#[derive(Clone)]
+pub struct Board {
+    dimensions: Dimensions,
+    cells: Vec<CellState>,
+    row_constraints: Vec<Constraint>,
+    col_constraints: Vec<Constraint>,
+    row_candidates: Vec<Vec<Line>>,
+    col_candidates: Vec<Vec<Line>>,
+    // Lines whose clue hasn't been given yet put no constraint on their cells, see `add_clue`
+    row_clued: Vec<bool>,
+    col_clued: Vec<bool>,
+    config: SolveConfig,
+    stats: SolveStats,
+    #[cfg(feature = "profiling")]
+    timings: LineTimings
+}
+
+impl Board {
+    pub fn new(constraints: Constraints, dimensions: Dimensions) -> Self {
+        Board::with_config(constraints, dimensions, SolveConfig::default())
+    }
+
+    /// Like `new`, but first rejects clues whose row and column totals can't agree, before any
+    /// candidates are generated.
+    pub fn try_new(constraints: Constraints, dimensions: Dimensions) -> Result<Self, SolverError> {
+        constraints.check_totals()?;
+        Ok(Board::new(constraints, dimensions))
+    }
+
+    pub fn with_config(constraints: Constraints, dimensions: Dimensions, config: SolveConfig) -> Self {
+        debug_assert!(
+            constraints.matches(&dimensions),
+            "{} row and {} column clues given for a {}x{} board",
+            constraints.num_rows(),
+            constraints.num_cols(),
+            dimensions.num_rows,
+            dimensions.num_cols
+        );
+
+        let row_length = dimensions.num_cols;
+        let col_length = dimensions.num_rows;
+
+        let row_trivial = constraints.rows
+            .iter()
+            .map(|constraint| Line::trivial(row_length, constraint))
+            .collect::<Vec<Option<Line>>>();
+        let col_trivial = constraints.cols
+            .iter()
+            .map(|constraint| Line::trivial(col_length, constraint))
+            .collect::<Vec<Option<Line>>>();
+        
+        let (row_candidates, col_candidates) = if config.streaming {
+            (Vec::new(), Vec::new())
+        } else {
+            let row_candidates = zip(&constraints.rows, &row_trivial)
+                .map(|(constraint, trivial)| match trivial {
+                    Some(line) => vec![line.clone()],
+                    None => Line::generate_initial_candidates(row_length, constraint, config.min_gap)
+                })
+                .collect::<Vec<Vec<Line>>>();
+            let col_candidates = zip(&constraints.cols, &col_trivial)
+                .map(|(constraint, trivial)| match trivial {
+                    Some(line) => vec![line.clone()],
+                    None => Line::generate_initial_candidates(col_length, constraint, config.min_gap)
+                })
+                .collect::<Vec<Vec<Line>>>();
+            (row_candidates, col_candidates)
+        };
+
+        let cells = vec![CellState::Unknown; dimensions.num_cols * dimensions.num_rows];
+
+        let stats = SolveStats {
+            candidates_generated: row_candidates.iter().chain(&col_candidates).map(Vec::len).sum(),
+            ..SolveStats::default()
+        };
+
+        let mut board = Board { 
+            dimensions, 
+            cells,
+            row_constraints: constraints.rows, 
+            col_constraints: constraints.cols, 
+            row_candidates, 
+            col_candidates,
+            row_clued: vec![true; dimensions.num_rows],
+            col_clued: vec![true; dimensions.num_cols],
+            config,
+            stats,
+            #[cfg(feature = "profiling")]
+            timings: LineTimings::new(dimensions)
+        };
+
+        board.stats.hardest_line.most_candidates = match board.worst_case_line() {
+            (_, _, 0) => None,
+            line => Some(line)
+        };
+
+        // Trivial lines are already done, so write them to the grid right away
+        for (idx, line) in row_trivial.iter().enumerate() {
+            if let Some(line) = line {
+                board.or_line(idx, Axis::Row, line);
+            }
+        }
+        for (idx, line) in col_trivial.iter().enumerate() {
+            if let Some(line) = line {
+                board.or_line(idx, Axis::Col, line);
+            }
+        }
+
+        board
+    }
+
+    /// The cell in row `row` (counted from the top) and column `col` (counted from the left).
+    pub fn cell(&self, row: usize, col: usize) -> CellState {
+        self.cells[row * self.dimensions.num_cols + col]
+    }
+
+    /// The same cell as `cell(y, x)`: `x` is the column, counted from the left, and `y` the row,
+    /// counted from the top. Note that `hintsX` in the benchmark data are the row clues, i.e. they
+    /// run along `y`.
+    pub fn cell_xy(&self, x: usize, y: usize) -> CellState {
+        self.cell(y, x)
+    }
+
+    /// Sets a single cell, e.g. one the user filled in. Nothing is checked here, a cell that
+    /// contradicts the clues makes the next `solve` end in `SolveOutcome::Contradiction`.
+    pub fn set_cell(&mut self, row: usize, col: usize, state: CellState) {
+        self.cells[row * self.dimensions.num_cols + col] = state;
+    }
+
+    /// A board where no line has a clue yet, so every cell is unknown. Reveal the clues one at a
+    /// time with `add_clue`.
+    pub fn unclued(dimensions: Dimensions, config: SolveConfig) -> Self {
+        let num_candidate_lists = |num_lines: usize| {
+            if config.streaming { 0 } else { num_lines }
+        };
+
+        Board {
+            dimensions,
+            cells: vec![CellState::Unknown; dimensions.num_cols * dimensions.num_rows],
+            row_constraints: vec![Constraint::new(vec![]); dimensions.num_rows],
+            col_constraints: vec![Constraint::new(vec![]); dimensions.num_cols],
+            row_candidates: vec![Vec::new(); num_candidate_lists(dimensions.num_rows)],
+            col_candidates: vec![Vec::new(); num_candidate_lists(dimensions.num_cols)],
+            row_clued: vec![false; dimensions.num_rows],
+            col_clued: vec![false; dimensions.num_cols],
+            config,
+            stats: SolveStats::default(),
+            #[cfg(feature = "profiling")]
+            timings: LineTimings::new(dimensions)
+        }
+    }
+
+    /// A random puzzle of `num_rows` by `num_cols` where each cell is full with a probability of
+    /// `fill_ratio`, returned solved: the clues are the puzzle and the grid its solution. The
+    /// cells are drawn from a xorshift generator seeded with `seed`, so the same arguments give
+    /// the same puzzle on every platform. The puzzle isn't guaranteed to have a unique solution.
+    pub fn generate(num_rows: usize, num_cols: usize, fill_ratio: f64, seed: u64) -> Board {
+        // Spread the seed over all bits first, xorshift never leaves a state of zero
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15).wrapping_mul(0xbf58_476d_1ce4_e5b9) | 1;
+        // The top 53 bits as a fraction in [0, 1)
+        let mut next = || (xorshift(&mut state) >> 11) as f64 / (1u64 << 53) as f64;
+
+        let grid = (0..num_rows)
+            .map(|_| {
+                (0..num_cols)
+                    .map(|_| if next() < fill_ratio { CellState::Full } else { CellState::Empty })
+                    .collect()
+            })
+            .collect::<Vec<Vec<CellState>>>();
+
+        let (constraints, dimensions) = Board::clues_of(&grid);
+        let mut board = Board::new(constraints, dimensions);
+        board.apply_solution(&grid).expect("a grid matches the clues derived from it");
+        board
+    }
+
+    /// Derives the clues of `solution` and returns the unsolved puzzle, but only if propagation
+    /// alone stalls on it while `solution` is still the clues' only solution, i.e. solving it
+    /// takes guessing. Meant for building test puzzles that exercise `solve_any`.
+    pub fn from_solution_with_noise(solution: &[Vec<CellState>]) -> Option<Board> {
+        let (constraints, dimensions) = Board::clues_of(solution);
+        let board = Board::new(constraints, dimensions);
+
+        let mut propagated = board.clone();
+        if propagated.solve() != SolveOutcome::Stalled {
+            return None
+        }
+        let mut solutions = Vec::new();
+        propagated.search_solutions(2, &mut solutions);
+        (solutions.len() == 1).then_some(board)
+    }
+
+    /// Reads a grid written by `to_rle` and returns a board with the grid's clues and the grid
+    /// filled in. Every cell has to be full or empty, every run at least one cell long and both
+    /// sides of the grid at most `MAX_DIMENSION`.
+    pub fn from_rle(rle: &str) -> Result<Board, SolverError> {
+        let invalid = || SolverError::InvalidRle(rle.to_string());
+        let too_large = |size| SolverError::DimensionTooLarge { size, max: crate::MAX_DIMENSION };
+
+        let mut grid = Vec::new();
+        for row in rle.split('|') {
+            if grid.len() == crate::MAX_DIMENSION {
+                return Err(too_large(rle.split('|').count()))
+            }
+            let mut cells = Vec::new();
+            let mut count = String::new();
+            for c in row.chars() {
+                if c.is_ascii_digit() {
+                    count.push(c);
+                    continue
+                }
+                let state = match c {
+                    '#' => CellState::Full,
+                    '.' => CellState::Empty,
+                    _ => return Err(invalid())
+                };
+                let count = match std::mem::take(&mut count).parse::<usize>() {
+                    Ok(0) | Err(_) => return Err(invalid()),
+                    Ok(count) => count
+                };
+                // Checked before extending, a huge count would otherwise be allocated first
+                let width = cells.len().saturating_add(count);
+                if width > crate::MAX_DIMENSION {
+                    return Err(too_large(width))
+                }
+                cells.extend(std::iter::repeat_n(state, count));
+            }
+            if !count.is_empty() || cells.is_empty() {
+                // A count without a cell after it, or a row without any cells
+                return Err(invalid())
+            }
+            grid.push(cells);
+        }
+        if grid.iter().any(|row| row.len() != grid[0].len()) {
+            return Err(invalid())
+        }
+
+        let (constraints, dimensions) = Board::clues_of(&grid);
+        let mut board = Board::new(constraints, dimensions);
+        board.apply_solution(&grid)?;
+        Ok(board)
+    }
+
+    /// The clues and size of a board whose solution is `grid`.
+    pub(crate) fn clues_of(grid: &[Vec<CellState>]) -> (Constraints, Dimensions) {
+        let num_rows = grid.len();
+        let num_cols = grid.first().map_or(0, Vec::len);
+
+        let row_constraints = grid
+            .iter()
+            .map(|row| Line::new(row.clone()).to_constraint())
+            .collect();
+        let col_constraints = (0..num_cols)
+            .map(|col| Line::new(grid.iter().map(|row| row[col]).collect()).to_constraint())
+            .collect();
+        (Constraints::new(col_constraints, row_constraints), Dimensions::new(num_rows, num_cols))
+    }
+
+    /// Sets the clue of line `idx` along `axis`, replacing any earlier one, and propagates.
+    /// Returns how many cells became determined.
+    pub fn add_clue(&mut self, axis: Axis, idx: usize, clue: Constraint) -> usize {
+        match axis {
+            Axis::Row => {
+                self.row_constraints[idx] = clue;
+                self.row_clued[idx] = true;
+            },
+            Axis::Col => {
+                self.col_constraints[idx] = clue;
+                self.col_clued[idx] = true;
+            }
+        }
+        self.regenerate_candidates(axis, idx);
+
+        let num_unknown = self.num_unknown();
+        self.solve();
+        num_unknown - self.num_unknown()
+    }
+
+    // TODO: Enable branching to enable guessing
+    pub fn solve(&mut self) -> SolveOutcome {
+        self.propagate(|_| {})
+    }
+
+    /// Solves with the lines crossing the window of `rows` and `cols` first: only those lines are
+    /// propagated for as long as that determines new cells, and the full solve only runs if the
+    /// window still has unknown cells afterwards. Every deduction is sound for the whole board,
+    /// the window is just worked on first. Returns the window's cells, one `Vec` per row.
+    pub fn solve_window(&mut self, rows: Range<usize>, cols: Range<usize>) -> Vec<Vec<CellState>> {
+        let window = |board: &Board| {
+            rows.clone()
+                .map(|row| cols.clone().map(|col| board.cell(row, col)).collect())
+                .collect::<Vec<Vec<CellState>>>()
+        };
+
+        loop {
+            let num_unknown = self.num_unknown();
+            for (axis, lines) in [(Axis::Row, rows.clone()), (Axis::Col, cols.clone())] {
+                for idx in lines {
+                    if !self.is_clued(axis, idx) {
+                        continue
+                    }
+                    // No arrangement left, let the full solve report it
+                    let Some(forced) = self.forced_line(axis, idx) else {
+                        self.solve();
+                        return window(self)
+                    };
+                    let combined = &self.to_line(idx, axis) | &forced;
+                    self.or_line(idx, axis, &combined);
+                }
+            }
+            if self.num_unknown() == num_unknown {
+                break
+            }
+        }
+
+        if window(self).iter().flatten().any(CellState::is_unknown) {
+            self.solve();
+        }
+        window(self)
+    }
+
+    /// Fills in at most `max_reveals` of the cells `solve` would determine and returns how many
+    /// were filled in. The solve runs on a copy and the newly determined cells are taken in
+    /// reading order, row by row from the top left, so the same board always reveals the same
+    /// cells.
+    pub fn solve_partial(&mut self, max_reveals: usize) -> usize {
+        let mut solved = self.clone();
+        solved.solve();
+
+        let reveals = (0..self.cells.len())
+            .filter(|&idx| self.cells[idx].is_unknown() && solved.cells[idx].is_determined())
+            .take(max_reveals)
+            .collect::<Vec<usize>>();
+        for &idx in &reveals {
+            self.cells[idx] = solved.cells[idx];
+        }
+        self.update_candidates(Axis::Row);
+        self.update_candidates(Axis::Col);
+
+        reveals.len()
+    }
+
+    /// Same as `solve`, but also records `(total_row_candidates, total_col_candidates)` after
+    /// every pass. In streaming mode no candidates are stored, so every entry is `(0, 0)`.
+    pub fn solve_with_trajectory(&mut self) -> (SolveOutcome, Vec<(usize, usize)>) {
+        let mut trajectory = Vec::new();
+        let outcome = self.propagate(|board| trajectory.push(board.num_candidates()));
+        (outcome, trajectory)
+    }
+
+    /// Same as `solve`, but also returns a copy of the board as it was before the first pass and
+    /// after every pass, e.g. for `Board::to_animated_svg`.
+    pub fn solve_with_frames(&mut self) -> (SolveOutcome, Vec<Board>) {
+        let mut frames = vec![self.clone()];
+        let outcome = self.propagate(|board| frames.push(board.clone()));
+        (outcome, frames)
+    }
+
+    /// Same as `solve`, but also returns the cells each pass determined as a JSON patch,
+    /// `{"ops": [{"row": 0, "col": 2, "state": 1}, ...]}` with `1` for full and `0` for empty, for
+    /// broadcasting deductions to other clients. The cells that were determined before solving,
+    /// e.g. by lines with a single arrangement, come first in a patch of their own, so applying
+    /// every patch to an empty grid gives the board's final grid. Passes that determine nothing
+    /// don't get a patch.
+    pub fn solve_with_patches(&mut self) -> (SolveOutcome, Vec<String>) {
+        let mut known = vec![CellState::Unknown; self.cells.len()];
+        let mut patches = Vec::new();
+
+        patches.extend(self.patch_since(&mut known));
+        let outcome = self.propagate(|board| patches.extend(board.patch_since(&mut known)));
+        (outcome, patches)
+    }
+
+    /// The JSON patch of `solve_with_patches` for the cells that are determined now but not in
+    /// `known`, which is brought up to date. `None` if there are no such cells.
+    fn patch_since(&self, known: &mut [CellState]) -> Option<String> {
+        let num_cols = self.dimensions.num_cols;
+        let ops = zip(known, &self.cells)
+            .enumerate()
+            .filter(|(_, (before, after))| before.is_unknown() && after.is_determined())
+            .map(|(idx, (before, &after))| {
+                *before = after;
+                format!("{{\"row\": {}, \"col\": {}, \"state\": {}}}", idx / num_cols, idx % num_cols, i8::from(after))
+            })
+            .collect::<Vec<String>>();
+        (!ops.is_empty()).then(|| format!("{{\"ops\": [{}]}}", ops.join(", ")))
+    }
+
+    /// The solve loop, calling `on_pass` with the board after every single-direction pass.
+    fn propagate(&mut self, on_pass: impl FnMut(&Self)) -> SolveOutcome {
+        let outcome = self.run_passes(on_pass);
+        self.stats.invalid_cells = self.invalid_cells();
+        outcome
+    }
+
+    fn run_passes(&mut self, mut on_pass: impl FnMut(&Self)) -> SolveOutcome {
+        let mut state = PassState::default();
+        loop {
+            if let Some(outcome) = self.next_pass(&mut state, &mut on_pass) {
+                return outcome
+            }
+        }
+    }
+
+    /// Runs the next single-direction pass of the solve loop and returns how solving ended, or
+    /// `None` if it should go on.
+    fn next_pass(&mut self, state: &mut PassState, on_pass: &mut impl FnMut(&Self)) -> Option<SolveOutcome> {
+        if self.num_unknown() == 0 {
+            return Some(self.stopped_outcome())
+        }
+
+        if state.unresolved.is_none() {
+            self.stats.hardest_line.most_passes = None;
+            state.unresolved = Some(self.unresolved_lines());
+        }
+
+        let min_progress = self.config.min_progress_per_cycle.max(1);
+        let axis = state.axis;
+        let progress = match self.merge_axis(axis) {
+            Ok(progress) => progress,
+            Err(SolverError::NoArrangement { index, axis }) => return Some(SolveOutcome::Contradiction { index, axis }),
+            Err(_) => unreachable!("propagation only fails with NoArrangement")
+        };
+        state.cycle_progress += progress;
+        self.stats.passes += 1;
+        state.passes += 1;
+        self.track_resolved_lines(state);
+        on_pass(self);
+
+        if self.config.adaptive_direction {
+            if progress >= min_progress {
+                state.stalled_passes = 0;
+                return None
+            }
+            state.stalled_passes += 1;
+            if state.stalled_passes == 2 {
+                // Neither direction makes progress anymore
+                return Some(self.stopped_outcome())
+            }
+        } else if axis == Axis::Col {
+            // A full row + column cycle is done. A single pass may make no progress while the
+            // other direction still can, so stalls are only judged per cycle.
+            if state.cycle_progress < min_progress {
+                // Board has multiple solutions
+                return Some(self.stopped_outcome())
+            }
+            state.cycle_progress = 0;
+        }
+
+        state.axis = axis.other();
+        None
+    }
+
+    fn unresolved_lines(&self) -> Vec<(usize, Axis)> {
+        (0..self.dimensions.num_rows)
+            .map(|idx| (idx, Axis::Row))
+            .chain((0..self.dimensions.num_cols).map(|idx| (idx, Axis::Col)))
+            .filter(|&(idx, axis)| !self.to_line(idx, axis).is_complete())
+            .collect()
+    }
+
+    /// Drops the lines the last pass fully determined from `state.unresolved`, keeping the one
+    /// that took the most passes in `stats.hardest_line`.
+    fn track_resolved_lines(&mut self, state: &mut PassState) {
+        let Some(unresolved) = state.unresolved.as_mut() else {
+            return
+        };
+
+        let mut most_passes = self.stats.hardest_line.most_passes;
+        unresolved.retain(|&(idx, axis)| {
+            if !self.to_line(idx, axis).is_complete() {
+                return true
+            }
+            if most_passes.is_none_or(|(_, _, passes)| state.passes > passes) {
+                most_passes = Some((idx, axis, state.passes));
+            }
+            false
+        });
+        self.stats.hardest_line.most_passes = most_passes;
+    }
+
+    /// How solving ended once propagation stops. A grid with `Invalid` cells is a contradiction,
+    /// reported at the row of the first one, and so is a full grid with a line that doesn't
+    /// match its clue, e.g. when lines were written before any candidates were checked.
+    fn stopped_outcome(&self) -> SolveOutcome {
+        if let Some(idx) = self.cells.iter().position(|&cell| cell == CellState::Invalid) {
+            SolveOutcome::Contradiction { index: idx / self.dimensions.num_cols, axis: Axis::Row }
+        } else if let Some((index, axis)) = self.mismatched_line() {
+            SolveOutcome::Contradiction { index, axis }
+        } else if self.is_solved() {
+            SolveOutcome::Solved
+        } else {
+            SolveOutcome::Stalled
+        }
+    }
+
+    /// Runs a single propagation pass over every line along `axis`. Returns `true` if it
+    /// determined at least one new cell, or the first line left without an arrangement.
+    pub fn merge_axis_once(&mut self, axis: Axis) -> Result<bool, SolverError> {
+        Ok(self.merge_axis(axis)? > 0)
+    }
+
+    /// One propagation pass along `axis`, returning the number of newly determined cells.
+    fn merge_axis(&mut self, axis: Axis) -> Result<usize, SolverError> {
+        let num_unknown = self.num_unknown();
+        self.update_candidates(axis);
+        self.update_cells(axis)?;
+        Ok(num_unknown.saturating_sub(self.num_unknown()))
+    }
+
+    /// Solves the board and writes the cells, row by row, to the start of `out` without
+    /// allocating: `2` for full, `1` for empty and `0` for cells that are still undetermined.
+    /// Fails before solving if `out` has fewer entries than the board has cells.
+    pub fn solve_into(&mut self, out: &mut [u8]) -> Result<(), SolverError> {
+        if out.len() < self.cells.len() {
+            return Err(SolverError::BufferTooSmall { required: self.cells.len(), actual: out.len() })
+        }
+
+        self.solve();
+        for (byte, cell) in zip(out.iter_mut(), &self.cells) {
+            *byte = match cell {
+                CellState::Full => 2,
+                CellState::Empty => 1,
+                _ => 0
+            };
+        }
+        Ok(())
+    }
+
+    /// Runs propagation and, if that stalls, guesses cells depth-first until the first complete
+    /// grid that matches every clue is found. Returns `None` if the clues have no solution. The
+    /// board itself is only propagated, apply the returned grid with `apply_solution` if needed.
+    pub fn solve_any(&mut self) -> Option<Vec<Vec<CellState>>> {
+        self.solve();
+        if self.cells.iter().any(CellState::is_unknown) && !self.has_contradiction() {
+            self.stats.used_search = true;
+        }
+
+        let symmetries = if self.config.symmetry_pruning {
+            self.constraints().symmetries()
+        } else {
+            Vec::new()
+        };
+        let mut backtracks = 0;
+        let grid = self.search_any(&symmetries, &mut backtracks);
+        self.stats.backtracks += backtracks;
+        grid
+    }
+
+    fn search_any(&mut self, symmetries: &[Symmetry], backtracks: &mut usize) -> Option<Vec<Vec<CellState>>> {
+        if self.has_contradiction() {
+            return None
+        }
+
+        let Some(idx) = self.cells.iter().position(CellState::is_unknown) else {
+            let grid = self.grid();
+            return self.validate(&grid).ok().map(|_| grid)
+        };
+
+        let guesses = if self.config.weighted_guesses && self.full_likelihood(idx) < 0.5 {
+            [CellState::Empty, CellState::Full]
+        } else {
+            [CellState::Full, CellState::Empty]
+        };
+        // Only a grid that looks the same in the mirror is sure to have mirror image solutions
+        // below it
+        let mirrors = symmetries
+            .iter()
+            .filter(|&&symmetry| self.is_symmetric(symmetry))
+            .map(|&symmetry| self.mirror_idx(idx, symmetry))
+            .collect::<Vec<usize>>();
+        for (attempt, guess) in guesses.into_iter().enumerate() {
+            let mut branch = self.clone();
+            branch.cells[idx] = guess;
+            if attempt == 1 {
+                // The first guess failed, so by symmetry it fails for the mirrored cells too
+                for &mirror in &mirrors {
+                    branch.cells[mirror] = guess;
+                }
+            }
+            branch.solve();
+            if let Some(grid) = branch.search_any(symmetries, backtracks) {
+                return Some(grid)
+            }
+            *backtracks += 1;
+        }
+        None
+    }
+
+    /// Solves the board and returns the grid, `true` for full cells, if the clues have exactly
+    /// one solution. Propagation is tried first and guessing only used if it stalls. On success
+    /// the solution is written to the board.
+    pub fn solved_or_err(&mut self) -> Result<Vec<Vec<bool>>, SolverError> {
+        let (rows, cols) = self.line_feasibility();
+        if let Some(index) = rows.iter().position(|fits| !fits) {
+            return Err(SolverError::Infeasible { index, axis: Axis::Row })
+        }
+        if let Some(index) = cols.iter().position(|fits| !fits) {
+            return Err(SolverError::Infeasible { index, axis: Axis::Col })
+        }
+        Constraints::totals_match(&self.row_constraints, &self.col_constraints)?;
+
+        self.solve();
+        let mut solutions = Vec::new();
+        self.clone().search_solutions(2, &mut solutions);
+
+        match &solutions[..] {
+            [] => Err(SolverError::Contradiction),
+            [solution] => {
+                self.apply_solution(solution)?;
+                Ok(solution
+                    .iter()
+                    .map(|row| row.iter().map(CellState::is_full).collect())
+                    .collect())
+            },
+            _ => Err(SolverError::Ambiguous)
+        }
+    }
+
+    /// Collects complete grids that match every clue into `solutions`, guessing `Full` before
+    /// `Empty`, until `limit` have been found.
+    fn search_solutions(&mut self, limit: usize, solutions: &mut Vec<Vec<Vec<CellState>>>) {
+        if self.has_contradiction() {
+            return
+        }
+
+        let Some(idx) = self.cells.iter().position(CellState::is_unknown) else {
+            let grid = self.grid();
+            if self.validate(&grid).is_ok() {
+                solutions.push(grid);
+            }
+            return
+        };
+
+        for guess in [CellState::Full, CellState::Empty] {
+            if solutions.len() >= limit {
+                return
+            }
+            let mut branch = self.clone();
+            branch.cells[idx] = guess;
+            branch.solve();
+            branch.search_solutions(limit, solutions);
+        }
+    }
+
+    /// Index of the cell `idx` is flipped onto by `symmetry`.
+    fn mirror_idx(&self, idx: usize, symmetry: Symmetry) -> usize {
+        let (num_rows, num_cols) = (self.dimensions.num_rows, self.dimensions.num_cols);
+        let (row, col) = (idx / num_cols, idx % num_cols);
+        let (row, col) = match symmetry {
+            Symmetry::LeftRight => (row, num_cols - 1 - col),
+            Symmetry::TopBottom => (num_rows - 1 - row, col),
+            Symmetry::HalfTurn => (num_rows - 1 - row, num_cols - 1 - col)
+        };
+        row * num_cols + col
+    }
+
+    fn is_symmetric(&self, symmetry: Symmetry) -> bool {
+        (0..self.cells.len()).all(|idx| self.cells[idx] == self.cells[self.mirror_idx(idx, symmetry)])
+    }
+
+    /// Estimates how likely the cell at `idx` is to be full: the fraction of its row's remaining
+    /// candidates that have it full, averaged with the same fraction for its column. Without
+    /// stored candidates (streaming mode) every cell is `0.5`.
+    fn full_likelihood(&self, idx: usize) -> f64 {
+        let row = idx / self.dimensions.num_cols;
+        let col = idx % self.dimensions.num_cols;
+        let fraction_full = |candidates: &[Line], pos: usize| {
+            if candidates.is_empty() {
+                return 0.5
+            }
+            let num_full = candidates.iter().filter(|line| line.cell(pos).is_full()).count();
+            num_full as f64 / candidates.len() as f64
+        };
+
+        (fraction_full(self.candidates(Axis::Row, row), col) + fraction_full(self.candidates(Axis::Col, col), row)) / 2.0
+    }
+
+    /// Whether some cell is `Invalid` or some line has no arrangement left that agrees with
+    /// the grid.
+    fn has_contradiction(&mut self) -> bool {
+        if self.cells.contains(&CellState::Invalid) {
+            return true
+        }
+
+        if self.config.streaming {
+            let row_length = self.dimensions.num_cols;
+            let col_length = self.dimensions.num_rows;
+            let rows_ok = self.row_constraints
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| self.is_clued(Axis::Row, *idx))
+                .all(|(idx, constraint)| Line::streamed_sum(row_length, constraint, &self.to_line(idx, Axis::Row), self.config.min_gap).is_some());
+            let cols_ok = self.col_constraints
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| self.is_clued(Axis::Col, *idx))
+                .all(|(idx, constraint)| Line::streamed_sum(col_length, constraint, &self.to_line(idx, Axis::Col), self.config.min_gap).is_some());
+            return !(rows_ok && cols_ok)
+        }
+
+        self.update_candidates(Axis::Row);
+        self.update_candidates(Axis::Col);
+        let rows_empty = zip(&self.row_candidates, &self.row_clued).any(|(candidates, &clued)| clued && candidates.is_empty());
+        let cols_empty = zip(&self.col_candidates, &self.col_clued).any(|(candidates, &clued)| clued && candidates.is_empty());
+        rows_empty || cols_empty
+    }
+
+    /// A cheap lower bound on the passes `solve` needs from the current state. `0` if the board
+    /// is already solved. Otherwise the first pass works on rows, and a row with no known cells
+    /// and more than one arrangement can't be pinned down by it, so at least a second pass is
+    /// needed.
+    pub fn min_passes_estimate(&self) -> usize {
+        if self.is_solved() {
+            return 0
+        }
+
+        let needs_col_pass = zip(&self.row_constraints, self.cells.chunks(self.dimensions.num_cols))
+            .any(|(constraint, row)| {
+                row.iter().all(CellState::is_unknown) && constraint.num_arrangements(self.dimensions.num_cols) > 1
+            });
+        if needs_col_pass { 2 } else { 1 }
+    }
+
+    /// The grid with what every clue forces on its own, as in `Line::overlap`, added to the
+    /// cells already known. Each line is looked at once and nothing is carried over between rows
+    /// and columns, so this shows only the most basic technique. Lines whose clue doesn't fit are
+    /// left as they are and conflicting lines show up as `Invalid` cells. The board itself isn't
+    /// changed.
+    pub fn overlap_only(&self) -> Vec<Vec<CellState>> {
+        let mut board = self.clone();
+        for axis in [Axis::Row, Axis::Col] {
+            for idx in (0..self.line_length(axis.other())).filter(|&idx| self.is_clued(axis, idx)) {
+                if let Some(overlap) = Line::overlap(self.line_length(axis), self.constraint_for(idx, axis)) {
+                    let combined = &board.to_line(idx, axis) | &overlap;
+                    board.or_line(idx, axis, &combined);
+                }
+            }
+        }
+        board.grid()
+    }
+
+    /// Whether `overlap_only` already determines every cell, i.e. the puzzle needs none of the
+    /// full engine. No candidates are filtered, so this is a quick check.
+    pub fn is_trivially_solvable(&self) -> bool {
+        let (rows_fit, cols_fit) = self.line_feasibility();
+        rows_fit.iter().chain(&cols_fit).all(|&fits| fits)
+            && self.overlap_only().iter().flatten().all(CellState::is_determined)
+    }
+
+    /// Checks every line on its own: can its clue be arranged at all in the line's length?
+    /// Returns the per-row and per-column results.
+    pub fn line_feasibility(&self) -> (Vec<bool>, Vec<bool>) {
+        let rows = self.row_constraints
+            .iter()
+            .map(|constraint| constraint.fits(self.dimensions.num_cols))
+            .collect();
+        let cols = self.col_constraints
+            .iter()
+            .map(|constraint| constraint.fits(self.dimensions.num_rows))
+            .collect();
+
+        (rows, cols)
+    }
+
+    /// The state the cell at `row`, `col` must have given the current grid, judged from its row
+    /// and its column on their own. `None` if neither line forces it yet. Nothing is written to
+    /// the board.
+    pub fn is_cell_forced(&self, row: usize, col: usize) -> Option<CellState> {
+        let cell = self.cells[row * self.dimensions.num_cols + col];
+        if cell.is_determined() {
+            return Some(cell)
+        }
+
+        [(Axis::Row, row, col), (Axis::Col, col, row)]
+            .into_iter()
+            .filter(|&(axis, idx, _)| self.is_clued(axis, idx))
+            .filter_map(|(axis, idx, pos)| self.forced_line(axis, idx).map(|line| line.cell(pos)))
+            .find(CellState::is_determined)
+    }
+
+    /// Applies the deductions of a single line: the first clued line, rows before columns, whose
+    /// clue forces a cell that is still unknown. Returns which cells that line filled in, or
+    /// `None` if no line forces anything new.
+    pub fn fill_forced_then_stop(&mut self) -> Option<Deduction> {
+        let (axis, idx, positions) = [Axis::Row, Axis::Col]
+            .into_iter()
+            .flat_map(|axis| (0..self.line_length(axis.other())).map(move |idx| (axis, idx)))
+            .filter(|&(axis, idx)| self.is_clued(axis, idx))
+            .find_map(|(axis, idx)| {
+                let current = self.to_line(idx, axis);
+                let forced = self.forced_line(axis, idx)?;
+                let positions = (0..current.len())
+                    .filter(|&pos| current.cell(pos).is_unknown() && forced.cell(pos).is_determined())
+                    .map(|pos| (pos, forced.cell(pos)))
+                    .collect::<Vec<(usize, CellState)>>();
+                (!positions.is_empty()).then_some((axis, idx, positions))
+            })?;
+
+        let cells = positions
+            .into_iter()
+            .map(|(pos, state)| {
+                let (row, col) = match axis {
+                    Axis::Row => (idx, pos),
+                    Axis::Col => (pos, idx)
+                };
+                self.set_cell(row, col, state);
+                (row, col)
+            })
+            .collect();
+        self.update_candidates(Axis::Row);
+        self.update_candidates(Axis::Col);
+
+        Some(Deduction { axis, index: idx, cells })
+    }
+
+    /// What the clue of line `idx` forces given the current grid, without changing the board.
+    /// `None` if no arrangement agrees with the grid.
+    fn forced_line(&self, axis: Axis, idx: usize) -> Option<Line> {
+        let current = self.to_line(idx, axis);
+        if self.config.streaming {
+            Line::streamed_sum(current.len(), self.constraint_for(idx, axis), &current, self.config.min_gap)
+        } else {
+            self.candidates(axis, idx)
+                .iter()
+                .filter(|line| line.agrees_with(&current))
+                .cloned()
+                .reduce(|sum, line| &sum & &line)
+        }
+    }
+
+    /// Fills in a whole line if the clue and the current grid leave it a single arrangement.
+    /// The crossing lines' candidates are narrowed down to match the revealed cells. If the line
+    /// isn't forced the board is left as it was.
+    pub fn reveal_line(&mut self, axis: Axis, idx: usize) -> Result<(), SolverError> {
+        let line = self.to_line(idx, axis);
+        let solved = if self.config.streaming {
+            let constraint = &self.clues(axis)[idx];
+
+            let mut matching = Line::arrangements(line.len(), constraint, self.config.min_gap)
+                .filter(|candidate| candidate.equivalient(&line));
+            match (matching.next(), matching.next()) {
+                (Some(solved), None) => Some(solved),
+                _ => None
+            }
+        } else {
+            let mut matching = self.candidates(axis, idx)
+                .iter()
+                .filter(|candidate| candidate.equivalient(&line));
+            match (matching.next(), matching.next()) {
+                (Some(solved), None) => Some(solved.clone()),
+                _ => None
+            }
+        };
+
+        let solved = solved.ok_or(SolverError::LineNotForced { index: idx, axis })?;
+        if !self.config.streaming {
+            match axis {
+                Axis::Row => self.row_candidates[idx] = vec![solved.clone()],
+                Axis::Col => self.col_candidates[idx] = vec![solved.clone()]
+            }
+        }
+        self.or_line(idx, axis, &solved);
+        self.update_candidates(axis.other());
+        Ok(())
+    }
+
+    /// Resets every cell of line `idx` along `axis` to `Unknown`, e.g. to undo a line the user
+    /// filled in. The line's candidates and those of every crossing line are rebuilt from their
+    /// clues and the cells still on the grid, since pruning can't be undone. Other lines keep
+    /// their candidates, which only depend on their own cells.
+    pub fn clear_line(&mut self, axis: Axis, idx: usize) {
+        for pos in 0..self.line_length(axis) {
+            let (row, col) = match axis {
+                Axis::Row => (idx, pos),
+                Axis::Col => (pos, idx)
+            };
+            self.set_cell(row, col, CellState::Unknown);
+        }
+
+        self.regenerate_candidates(axis, idx);
+        for crossing in 0..self.line_length(axis) {
+            self.regenerate_candidates(axis.other(), crossing);
+        }
+    }
+
+    /// The current cell states, one `Vec` per row.
+    pub fn grid(&self) -> Vec<Vec<CellState>> {
+        self.cells
+            .chunks(self.dimensions.num_cols)
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    /// The smallest box containing every `Full` cell as `(min_row, max_row, min_col, max_col)`,
+    /// bounds inclusive. `None` if no cell is full.
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        let num_cols = self.dimensions.num_cols;
+        let (rows, cols): (Vec<usize>, Vec<usize>) = self.cells
+            .iter()
+            .positions(CellState::is_full)
+            .map(|idx| (idx / num_cols, idx % num_cols))
+            .unzip();
+
+        Some((*rows.iter().min()?, *rows.iter().max()?, *cols.iter().min()?, *cols.iter().max()?))
+    }
+
+    /// Whether `grid`, a partly filled in answer, can still be completed to a solution of the
+    /// clues: some solution must have every `Full` cell of `grid` full and every `Empty` cell
+    /// empty. `Unknown` cells are left open. A grid of the wrong size is never consistent.
+    pub fn is_consistent_with(&self, grid: &[Vec<CellState>]) -> bool {
+        if grid.len() != self.dimensions.num_rows || grid.iter().any(|row| row.len() != self.dimensions.num_cols) {
+            return false
+        }
+
+        let mut board = self.clone();
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate().filter(|(_, cell)| cell.is_determined()) {
+                let current = board.cell(row, col);
+                if current.is_determined() && current != cell {
+                    return false
+                }
+                board.set_cell(row, col, cell);
+            }
+        }
+
+        board.solve_any().is_some_and(|solution| {
+            zip(grid, &solution).all(|(given, solved)| {
+                zip(given, solved).all(|(given, solved)| !given.is_determined() || given == solved)
+            })
+        })
+    }
+
+    /// The first clued line, rows before columns, that doesn't match its clue on a grid without
+    /// unknown cells. Always `None` while cells are unknown.
+    fn mismatched_line(&self) -> Option<(usize, Axis)> {
+        if self.num_unknown() > 0 {
+            return None
+        }
+        [Axis::Row, Axis::Col]
+            .into_iter()
+            .flat_map(|axis| (0..self.clues(axis).len()).map(move |idx| (idx, axis)))
+            .find(|&(idx, axis)| {
+                self.is_clued(axis, idx)
+                    && !self.to_line(idx, axis).is_valid_with_gap(&self.clues(axis)[idx], self.config.min_gap)
+            })
+    }
+
+    /// Checks that `grid` is a complete solution: every row and column must match its clue.
+    /// Rows are checked before columns and the first mismatching line is reported.
+    pub fn validate(&self, grid: &[Vec<CellState>]) -> Result<(), SolverError> {
+        // Lines without a clue yet accept anything, so the totals can't be compared
+        if self.row_clued.iter().chain(&self.col_clued).all(|&clued| clued) {
+            Constraints::totals_match(&self.row_constraints, &self.col_constraints)?;
+        }
+
+        let num_rows = grid.len();
+        let num_cols = grid.first().map_or(0, |row| row.len());
+        if num_rows != self.dimensions.num_rows 
+            || grid.iter().any(|row| row.len() != self.dimensions.num_cols) {
+            return Err(SolverError::GridSizeMismatch {
+                expected: (self.dimensions.num_rows, self.dimensions.num_cols),
+                actual: (num_rows, num_cols)
+            })
+        }
+
+        for (idx, (row, constraint)) in zip(grid, &self.row_constraints).enumerate() {
+            if self.is_clued(Axis::Row, idx) && !Line::new(row.clone()).is_valid_with_gap(constraint, self.config.min_gap) {
+                return Err(SolverError::LineMismatch { index: idx, axis: Axis::Row })
+            }
+        }
+        for (idx, constraint) in self.col_constraints.iter().enumerate() {
+            let col = Line::new(grid.iter().map(|row| row[idx]).collect());
+            if self.is_clued(Axis::Col, idx) && !col.is_valid_with_gap(constraint, self.config.min_gap) {
+                return Err(SolverError::LineMismatch { index: idx, axis: Axis::Col })
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fills the board from an externally provided solution after checking it with `validate`.
+    pub fn apply_solution(&mut self, grid: &[Vec<CellState>]) -> Result<(), SolverError> {
+        self.validate(grid)?;
+
+        self.cells = grid.concat();
+        self.update_candidates(Axis::Row);
+        self.update_candidates(Axis::Col);
+        Ok(())
+    }
+
+    /// `(row, col)` of every cell that is currently `Invalid`.
+    pub fn invalid_cells(&self) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| **cell == CellState::Invalid)
+            .map(|(idx, _)| (idx / self.dimensions.num_cols, idx % self.dimensions.num_cols))
+            .collect()
+    }
+
+    pub fn stats(&self) -> &SolveStats {
+        &self.stats
+    }
+
+    /// Cumulative time spent filtering each line's candidates over every solve so far, one entry
+    /// per line, slowest first. Lines that are never filtered, e.g. every line in streaming mode,
+    /// report zero. Only available with the `profiling` feature, which doesn't work on wasm
+    /// since it reads the system clock.
+    #[cfg(feature = "profiling")]
+    pub fn timing_report(&self) -> Vec<LineTiming> {
+        let entries = |axis: Axis, times: &[std::time::Duration]| {
+            times
+                .iter()
+                .enumerate()
+                .map(|(index, &time)| LineTiming { axis, index, time })
+                .collect::<Vec<LineTiming>>()
+        };
+
+        let mut report = entries(Axis::Row, &self.timings.rows);
+        report.extend(entries(Axis::Col, &self.timings.cols));
+        report.sort_by_key(|timing| std::cmp::Reverse(timing.time));
+        report
+    }
+
+    fn to_line(&self, idx: usize, axis: Axis) -> Line {
+        match axis {
+            Axis::Row => {
+                let start = idx * self.dimensions.num_cols;
+                let end = start + self.dimensions.num_cols;
+                let cells = self.cells[start..end].to_vec();
+                Line::new(cells)
+            },
+            Axis::Col => {
+                let cells = (0..self.dimensions.num_rows)
+                    .map(|row_idx| self.cells[row_idx * self.dimensions.num_cols + idx])
+                    .collect();
+                Line::new(cells)
+            }
+        }
+    }
+
+    fn or_line(&mut self, idx: usize, axis: Axis, line: &Line) {
+        match axis {
+            Axis::Row => {
+                let start = idx * self.dimensions.num_cols;
+                let end = start + self.dimensions.num_cols;
+                self.cells[start..end].copy_from_slice(&line.cells());
+            },
+            Axis::Col => {
+                for (row_idx, cell) in line.cells().into_iter().enumerate() {
+                    self.cells[row_idx * self.dimensions.num_cols + idx] = self.cells[row_idx * self.dimensions.num_cols + idx] | cell
+                }
+            }
+        }
+    }
+
+    fn is_clued(&self, axis: Axis, idx: usize) -> bool {
+        match axis {
+            Axis::Row => self.row_clued[idx],
+            Axis::Col => self.col_clued[idx]
+        }
+    }
+
+    /// Length of the lines along `axis`.
+    fn line_length(&self, axis: Axis) -> usize {
+        match axis {
+            Axis::Row => self.dimensions.num_cols,
+            Axis::Col => self.dimensions.num_rows
+        }
+    }
+
+    fn line_candidates(&self, axis: Axis) -> &[Vec<Line>] {
+        match axis {
+            Axis::Row => &self.row_candidates,
+            Axis::Col => &self.col_candidates
+        }
+    }
+
+    /// Renders the board with the row clues to the left and the column clues stacked above.
+    ///
+    /// Every column is padded to the same display width (wide enough for the longest column
+    /// clue value plus a separating space) and the cell glyph is repeated to fill it, so clues
+    /// and cells line up in a monospace terminal.
+    pub fn to_labeled_string(&self) -> String {
+        let row_labels = self.row_constraints
+            .iter()
+            .map(|constraint| constraint.values.iter().join(" "))
+            .collect::<Vec<String>>();
+        let label_width = row_labels
+            .iter()
+            .map(|label| label.width())
+            .max()
+            .unwrap_or(0);
+
+        let glyph_width = [CellState::Full, CellState::Empty, CellState::Unknown, CellState::Invalid]
+            .iter()
+            .map(|cell| cell.to_string().width())
+            .max()
+            .unwrap_or(1);
+        let clue_width = self.col_constraints
+            .iter()
+            .flat_map(|constraint| constraint.values.iter())
+            .map(|value| value.to_string().width())
+            .max()
+            .unwrap_or(1);
+        // Round up to a whole number of glyphs so cells can be drawn by repeating the glyph
+        let cell_width = (clue_width + 1).div_ceil(glyph_width) * glyph_width;
+
+        let clue_depth = self.col_constraints
+            .iter()
+            .map(|constraint| constraint.values.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut s = String::new();
+        for depth in 0..clue_depth {
+            s.push_str(&" ".repeat(label_width));
+            for constraint in &self.col_constraints {
+                let offset = clue_depth - constraint.values.len();
+                let label = if depth >= offset {
+                    constraint.values[depth - offset].to_string()
+                } else {
+                    String::new()
+                };
+                s.push_str(&" ".repeat(cell_width - label.width()));
+                s.push_str(&label);
+            }
+            s.push('\n');
+        }
+
+        for (label, row) in zip(&row_labels, self.cells.chunks(self.dimensions.num_cols)) {
+            s.push_str(&" ".repeat(label_width - label.width()));
+            s.push_str(label);
+            for cell in row {
+                let glyph = cell.to_string();
+                s.push_str(&glyph.repeat(cell_width / glyph.width()));
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Renders the grid like `Display` but with the fixed-width ASCII cells from
+    /// `CellState::to_ascii`, so columns line up regardless of the terminal font.
+    pub fn to_ascii_string(&self) -> String {
+        let mut s = String::new();
+        for row in self.cells.chunks(self.dimensions.num_cols) {
+            s.push_str(&row.iter().map(|cell| cell.to_ascii()).join(""));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Run-length encodes the grid: each row as runs of a count followed by the cell in the
+    /// notation of `Line::to_compact_string`, e.g. `5.3#2.`, with rows separated by `|`. Much
+    /// shorter than one character per cell for sparse boards. Read it back with `from_rle`.
+    pub fn to_rle(&self) -> String {
+        self.cells
+            .chunks(self.dimensions.num_cols)
+            .map(|row| {
+                Line::new(row.to_vec())
+                    .to_compact_string()
+                    .chars()
+                    .chunk_by(|&c| c)
+                    .into_iter()
+                    .map(|(c, run)| format!("{}{}", run.count(), c))
+                    .join("")
+            })
+            .join("|")
+    }
+
+    /// Renders line `index` along `axis` with the `Display` glyphs, e.g. `█░█`. Panics if the
+    /// extracted line doesn't have the length the board's dimensions give that direction.
+    pub fn line_string(&self, axis: Axis, index: usize) -> String {
+        let line = self.to_line(index, axis);
+        assert_eq!(
+            line.len(),
+            self.line_length(axis),
+            "{} {} has the wrong length for a {}x{} board",
+            axis,
+            index,
+            self.dimensions.num_rows,
+            self.dimensions.num_cols
+        );
+        line.cells().iter().map(CellState::to_string).collect()
+    }
+
+    /// Renders the grid with the `Display` glyphs, each repeated `horizontal_scale` times. Terminal
+    /// cells are about twice as tall as wide, so a scale of 2 gives roughly square cells.
+    pub fn to_ascii_art(&self, horizontal_scale: usize) -> String {
+        let mut s = String::new();
+        for row in self.cells.chunks(self.dimensions.num_cols) {
+            for cell in row {
+                s.push_str(&cell.to_string().repeat(horizontal_scale));
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Renders `frames` as a single SVG that cycles through them, showing each for half a
+    /// second. Every frame is a `<g class="frame">` group of cell squares, shown in turn by a CSS
+    /// animation. All frames are expected to have the dimensions of the first one.
+    pub fn to_animated_svg(frames: &[Board]) -> String {
+        const CELL_SIZE: usize = 10;
+        const FRAME_SECONDS: f64 = 0.5;
+
+        let (width, height) = frames
+            .first()
+            .map(|board| (board.dimensions.num_cols * CELL_SIZE, board.dimensions.num_rows * CELL_SIZE))
+            .unwrap_or((0, 0));
+        let total_seconds = FRAME_SECONDS * frames.len() as f64;
+        let visible_percent = 100.0 / frames.len().max(1) as f64;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+            width, height
+        );
+        svg.push_str(&format!(
+            "<style>.frame{{opacity:0;animation:frames {}s step-end infinite}}@keyframes frames{{0%{{opacity:1}}{}%{{opacity:0}}}}</style>\n",
+            total_seconds, visible_percent
+        ));
+        svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n", width, height));
+
+        for (idx, frame) in frames.iter().enumerate() {
+            svg.push_str(&format!("<g class=\"frame\" style=\"animation-delay:{}s\">", FRAME_SECONDS * idx as f64));
+            svg.push_str(&frame.to_svg_cells(CELL_SIZE));
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// One `<rect>` per determined cell, `cell_size` units wide. Unknown cells are left out.
+    fn to_svg_cells(&self, cell_size: usize) -> String {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, cell)| {
+                let fill = match cell {
+                    CellState::Full => "black",
+                    CellState::Empty => "lightgray",
+                    CellState::Invalid => "red",
+                    CellState::Unknown => return None
+                };
+                let (row, col) = (idx / self.dimensions.num_cols, idx % self.dimensions.num_cols);
+                Some(format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"{3}\"/>",
+                    col * cell_size, row * cell_size, cell_size, fill
+                ))
+            })
+            .collect()
+    }
+
+    /// The clues of every line along `axis`.
+    pub fn clues(&self, axis: Axis) -> &[Constraint] {
+        match axis {
+            Axis::Row => &self.row_constraints,
+            Axis::Col => &self.col_constraints
+        }
+    }
+
+    /// The clue of line `idx` along `axis`.
+    pub fn constraint_for(&self, idx: usize, axis: Axis) -> &Constraint {
+        &self.clues(axis)[idx]
+    }
+
+    /// The arrangements still possible for line `idx` along `axis`, as of the last pass. Always
+    /// empty in streaming mode.
+    pub fn candidates(&self, axis: Axis, idx: usize) -> &[Line] {
+        if self.config.streaming {
+            return &[]
+        }
+        &self.line_candidates(axis)[idx]
+    }
+
+    /// Every line's remaining candidates along `axis` in the compact notation of
+    /// `Line::to_compact_string`, one `Vec` per line, for debugging propagation. The lists are
+    /// empty in streaming mode.
+    pub fn dump_candidates(&self, axis: Axis) -> Vec<Vec<String>> {
+        (0..self.clues(axis).len())
+            .map(|idx| self.candidates(axis, idx).iter().map(Line::to_compact_string).collect())
+            .collect()
+    }
+
+    /// The line with the most remaining candidates as `(index, axis, count)`, i.e. the one taking
+    /// up the most candidate storage and pruning time. Ties go to rows, then to the lower index.
+    /// In streaming mode no candidates are stored and this is `(0, Axis::Row, 0)`.
+    pub fn worst_case_line(&self) -> (usize, Axis, usize) {
+        [Axis::Row, Axis::Col]
+            .into_iter()
+            .flat_map(|axis| {
+                (0..self.clues(axis).len()).map(move |idx| (idx, axis, self.candidates(axis, idx).len()))
+            })
+            .fold((0, Axis::Row, 0), |worst, line| if line.2 > worst.2 { line } else { worst })
+    }
+
+    /// The natural log of every line's remaining candidate count, summed and divided by the
+    /// number of cells. On a fresh board this is a cheap difficulty estimate: the more ways the
+    /// lines can be arranged, the harder the puzzle tends to be. Lines with a single candidate
+    /// add nothing, and neither do lines in streaming mode, where no candidates are stored.
+    pub fn candidate_entropy(&self) -> f64 {
+        let num_cells = self.dimensions.num_rows * self.dimensions.num_cols;
+        if num_cells == 0 {
+            return 0.0
+        }
+
+        let total = [Axis::Row, Axis::Col]
+            .into_iter()
+            .flat_map(|axis| (0..self.clues(axis).len()).map(move |idx| self.candidates(axis, idx).len()))
+            .map(|count| (count.max(1) as f64).ln())
+            .sum::<f64>();
+        total / num_cells as f64
+    }
+
+    /// Returns a copy of the clues the board was built from.
+    pub fn constraints(&self) -> Constraints {
+        Constraints::new(self.col_constraints.clone(), self.row_constraints.clone())
+    }
+
+    /// Serializes the clues and the current grid in the same layout as the benchmark data:
+    /// `hintsX` holds the row clues, `hintsY` the column clues and `solution` encodes full cells
+    /// as `2`, empty cells as `1` and undetermined cells as `0`. Ranged clue values are written as
+    /// a `[min, max]` pair.
+    pub fn to_puzzle_json(&self) -> String {
+        let clues_to_json = |constraints: &[Constraint]| {
+            constraints
+                .iter()
+                .map(|constraint| {
+                    let values = constraint.values
+                        .iter()
+                        .map(|value| match value {
+                            ClueValue::Exact(value) => value.to_string(),
+                            ClueValue::Range(min, max) => format!("[{}, {}]", min, max)
+                        })
+                        .join(", ");
+                    format!("[{}]", values)
+                })
+                .join(", ")
+        };
+        let solution = self.cells
+            .chunks(self.dimensions.num_cols)
+            .map(|row| {
+                let values = row
+                    .iter()
+                    .map(|cell| match cell {
+                        CellState::Full => 2,
+                        CellState::Empty => 1,
+                        _ => 0
+                    })
+                    .join(", ");
+                format!("[{}]", values)
+            })
+            .join(", ");
+
+        format!(
+            "{{\"solution\": [{}], \"hintsX\": [{}], \"hintsY\": [{}]}}",
+            solution,
+            clues_to_json(&self.row_constraints),
+            clues_to_json(&self.col_constraints)
+        )
+    }
+
+    /// Replaces the candidates of line `idx` along `axis` with every arrangement of its clue
+    /// that agrees with the line's cells. Lines without a clue keep no candidates.
+    fn regenerate_candidates(&mut self, axis: Axis, idx: usize) {
+        if self.config.streaming || !self.is_clued(axis, idx) {
+            return
+        }
+
+        let current = self.to_line(idx, axis);
+        let mut candidates = Line::generate_initial_candidates(self.line_length(axis), &self.clues(axis)[idx], self.config.min_gap);
+        self.stats.candidates_generated += candidates.len();
+        candidates.retain(|line| line.equivalient(&current));
+
+        match axis {
+            Axis::Row => self.row_candidates[idx] = candidates,
+            Axis::Col => self.col_candidates[idx] = candidates
+        }
+    }
+
+    fn update_candidates(&mut self, axis: Axis) {
+        let lines = (0..self.line_candidates(axis).len())
+            .map(|idx| self.to_line(idx, axis))
+            .collect::<Vec<Line>>();
+
+        let clued = (0..lines.len())
+            .map(|idx| self.is_clued(axis, idx))
+            .collect::<Vec<bool>>();
+
+        let min_gap = self.config.min_gap;
+        let (constraints, line_candidates) = match axis {
+            Axis::Row => (&self.row_constraints, &mut self.row_candidates),
+            Axis::Col => (&self.col_constraints, &mut self.col_candidates)
+        };
+        let mut pruned = HashMap::<(bool, &Constraint, &Line), usize>::new();
+        for idx in 0..line_candidates.len() {
+            // A line whose cells already spell out its clue is done, so its candidates collapse
+            // to that line and it isn't checked again
+            let current = &lines[idx];
+            if clued[idx] && current.is_valid_with_gap(&constraints[idx], min_gap) {
+                if line_candidates[idx].len() != 1 {
+                    line_candidates[idx] = vec![current.clone()];
+                }
+                continue
+            }
+
+            // Only the cells known from the crossing lines are compared, lines with nothing known
+            // are skipped entirely
+            if !current.has_known_cells() {
+                continue
+            }
+
+            // Lines with the same clue and the same cells so far are left with the same
+            // candidates, so the first one of them is pruned and the rest copy its result
+            match pruned.entry((clued[idx], &constraints[idx], current)) {
+                Entry::Occupied(twin) => {
+                    line_candidates[idx] = line_candidates[*twin.get()].clone();
+                    continue
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert(idx);
+                }
+            }
+
+            #[cfg(feature = "profiling")]
+            let started = std::time::Instant::now();
+
+            self.stats.candidates_checked += line_candidates[idx].len();
+            line_candidates[idx].retain(|line| line.agrees_with(current));
+
+            #[cfg(feature = "profiling")]
+            match axis {
+                Axis::Row => self.timings.rows[idx] += started.elapsed(),
+                Axis::Col => self.timings.cols[idx] += started.elapsed()
+            }
+        }
+    }
+        
+    /// Writes what every clued line along `axis` forces to the grid. Fails without writing
+    /// anything if a line has no arrangement left.
+    fn update_cells(&mut self, axis: Axis) -> Result<(), SolverError> {
+        let length = self.line_length(axis);
+
+        let summed_lines = if self.config.streaming {
+            self.clues(axis)
+                .iter()
+                .enumerate()
+                .map(|(idx, constraint)| {
+                    let current = self.to_line(idx, axis);
+                    Line::streamed_sum(length, constraint, &current, self.config.min_gap)
+                })
+                .collect::<Vec<Option<Line>>>()
         } else {
-            for (row_idx, cell) in line.cells.iter().enumerate() {
-                self.cells[row_idx * self.dimensions.num_cols + idx] = self.cells[row_idx * self.dimensions.num_cols + idx] | *cell
+            self.line_candidates(axis)
+                .iter()
+                .map(|candidates| Line::sum(candidates))
+                .collect::<Vec<Option<Line>>>()
+        };
+
+        let mut forced_lines = Vec::with_capacity(summed_lines.len());
+        for (idx, line) in summed_lines.into_iter().enumerate() {
+            if !self.is_clued(axis, idx) {
+                continue
+            }
+            match line {
+                Some(line) => forced_lines.push((idx, line)),
+                None => return Err(SolverError::NoArrangement { index: idx, axis })
+            }
+        }
+
+        for (idx, line) in forced_lines {
+            self.or_line(idx, axis, &line);
+        }
+        Ok(())
+    }
+
+    fn num_unknown(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|cell| cell.is_unknown())
+            .count()
+    }
+
+    fn num_candidates(&self) -> (usize, usize) {
+        let count = |candidates: &[Vec<Line>]| candidates.iter().map(Vec::len).sum();
+        (count(&self.row_candidates), count(&self.col_candidates))
+    }
+
+    fn is_solved(&self) -> bool {
+        self.num_unknown() == 0 && !self.cells.contains(&CellState::Invalid)
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.dimensions.num_cols) {
+            writeln!(f, "{}", row.iter().join(""))?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Where a `SolverHandle` is at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HandleState {
+    /// More passes may still determine cells. Also the state of a handle that was stopped or ran
+    /// out of time before propagation was done.
+    Running,
+    /// Propagation can't determine any more cells, or ran into a contradiction. Solving the rest
+    /// takes guessing, e.g. with `Board::solve_any`.
+    Stalled,
+    /// Every cell is determined
+    Solved
+}
+
+/// Everything a UI needs to show a `SolverHandle`'s progress, see `SolverHandle::status`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandleStatus {
+    /// The best grid so far, one `Vec` per row
+    pub grid: Vec<Vec<CellState>>,
+    /// Number of cells that are no longer unknown
+    pub determined: usize,
+    pub state: HandleState
+}
+
+/// Runs `Board::solve` a few passes at a time, so a caller with a time budget (e.g. a browser
+/// tab between frames) can stop in between and still show what was found so far.
+pub struct SolverHandle {
+    board: Board,
+    pass_state: PassState,
+    outcome: Option<SolveOutcome>
+}
+
+impl SolverHandle {
+    pub fn new(board: Board) -> Self {
+        SolverHandle { board, pass_state: PassState::default(), outcome: None }
+    }
+
+    /// Runs up to `max_passes` single-direction passes and returns the state afterwards. Once the
+    /// solve is over further calls do nothing.
+    pub fn step(&mut self, max_passes: usize) -> HandleState {
+        for _ in 0..max_passes {
+            if self.outcome.is_some() {
+                break
+            }
+            self.outcome = self.board.next_pass(&mut self.pass_state, &mut |_| {});
+            if self.outcome.is_some() {
+                self.board.stats.invalid_cells = self.board.invalid_cells();
+            }
+        }
+        self.state()
+    }
+
+    /// How the solve ended, or `None` while it's still running.
+    pub fn outcome(&self) -> Option<SolveOutcome> {
+        self.outcome
+    }
+
+    pub fn status(&self) -> HandleStatus {
+        HandleStatus {
+            grid: self.board.grid(),
+            determined: self.board.cells.len() - self.board.num_unknown(),
+            state: self.state()
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+
+    fn state(&self) -> HandleState {
+        match self.outcome {
+            None => HandleState::Running,
+            Some(SolveOutcome::Solved) => HandleState::Solved,
+            Some(_) => HandleState::Stalled
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod cell_state {
+        use super::*;
+
+        #[test]
+        fn test_print() {
+            let full = CellState::Full;
+            let empty = CellState::Empty;
+            let unknown = CellState::Unknown;
+
+            println!("{} {} {}", &full, &empty, &unknown);
+        }
+
+        #[test]
+        fn test_conversions() {
+            for cell in [CellState::Full, CellState::Empty, CellState::Unknown, CellState::Invalid] {
+                assert_eq!(CellState::try_from(i8::from(cell)), Ok(cell));
+            }
+            assert_eq!(CellState::try_from(2), Err(SolverError::InvalidCellValue(2)));
+
+            assert_eq!(CellState::Full.as_bool(), Some(true));
+            assert_eq!(CellState::Empty.as_bool(), Some(false));
+            assert_eq!(CellState::Unknown.as_bool(), None);
+            assert_eq!(CellState::Invalid.as_bool(), None);
+        }
+
+        #[test]
+        fn test_grid_ints() {
+            let ints = vec![vec![1, 0, -1], vec![-2, 1, 1]];
+            let grid = vec![
+                vec![CellState::Full, CellState::Empty, CellState::Unknown],
+                vec![CellState::Invalid, CellState::Full, CellState::Full]
+            ];
+
+            assert_eq!(grid_from_ints(&ints), grid);
+            assert_eq!(grid_to_ints(&grid), ints);
+            assert_eq!(grid_to_ints(&grid_from_ints(&ints)), ints);
+            assert_eq!(grid_from_ints(&[vec![2, 300]]), vec![vec![CellState::Invalid; 2]]);
+        }
+
+        #[test]
+        fn test_predicates() {
+            let full = CellState::Full;
+            let empty = CellState::Empty;
+            let unknown = CellState::Unknown;
+            let invalid = CellState::Invalid;
+
+            assert!(full.is_determined() && full.is_full());
+            assert!(!full.is_unknown() && !full.is_empty());
+
+            assert!(empty.is_determined() && empty.is_empty());
+            assert!(!empty.is_unknown() && !empty.is_full());
+
+            assert!(unknown.is_unknown());
+            assert!(!unknown.is_determined() && !unknown.is_full() && !unknown.is_empty());
+
+            assert!(!invalid.is_determined() && !invalid.is_unknown());
+            assert!(!invalid.is_full() && !invalid.is_empty());
+        }
+
+        #[test]
+        fn test_and() {
+            let full = CellState::Full;
+            let empty = CellState::Empty;
+            let unknown = CellState::Unknown;
+
+            assert_eq!(full & unknown, unknown);
+            assert_eq!(empty & unknown, unknown);
+            assert_eq!(full & empty, unknown);
+            assert_eq!(full & full, full);
+            assert_eq!(empty & empty, empty);
+        }
+    
+        #[test]
+        fn test_or() {
+            let full = CellState::Full;
+            let empty = CellState::Empty;
+            let unknown = CellState::Unknown;
+            let invalid = CellState::Invalid;
+
+            assert_eq!(full | unknown, full);
+            assert_eq!(full | full, full);
+            assert_eq!(empty | unknown, empty);
+            assert_eq!(empty | empty, empty);
+            assert_eq!(full | empty, invalid);
+        }
+    }
+
+    mod constraint {
+        use super::*;
+
+        #[test]
+        fn test_from_positions() {
+            // ##.###..#. in a line of ten
+            let (starts, lengths) = ([0, 3, 8], [2, 3, 1]);
+            assert_eq!(Constraint::validate_positions(&starts, &lengths, 10), Ok(()));
+            assert_eq!(Constraint::from_positions(&starts, &lengths), Constraint::new(vec![2, 3, 1]));
+
+            // The second block starts inside the first, then right after it
+            assert_eq!(Constraint::validate_positions(&[0, 1], &[2, 2], 10), Err(SolverError::BlockOverlap { block: 1 }));
+            assert_eq!(Constraint::validate_positions(&[0, 2], &[2, 2], 10), Err(SolverError::BlockOverlap { block: 1 }));
+            assert_eq!(
+                Constraint::validate_positions(&[0, 8], &[2, 3], 10),
+                Err(SolverError::BlockOutOfRange { block: 1, length: 10 })
+            );
+            assert_eq!(
+                Constraint::validate_positions(&[0], &[2, 3], 10),
+                Err(SolverError::PositionCountMismatch { starts: 1, lengths: 2 })
+            );
+        }
+
+        #[test]
+        fn test_min_length() {
+            assert_eq!(Constraint::new(vec![2, 3, 1]).min_length(), 8);
+            assert_eq!(Constraint::new(vec![]).min_length(), 0);
+            assert_eq!(Constraint::with_clues(vec![ClueValue::Range(1, 4), ClueValue::Exact(2)]).min_length(), 4);
+            assert_eq!(Constraint::with_clues(vec![ClueValue::Range(1, 4), ClueValue::Exact(2)]).max_block(), 4);
+            assert_eq!(Constraint::new(vec![]).max_block(), 0);
+        }
+
+        #[test]
+        fn test_invalid_range() {
+            assert_eq!(ClueValue::range(2, 4), Ok(ClueValue::Range(2, 4)));
+            assert_eq!(ClueValue::range(3, 3), Ok(ClueValue::Range(3, 3)));
+            assert_eq!(ClueValue::range(3, 1), Err(SolverError::InvalidClueRange { min: 3, max: 1 }));
+            assert_eq!(ClueValue::range(0, 2), Err(SolverError::InvalidClueRange { min: 0, max: 2 }));
+
+            assert_eq!(
+                Constraint::try_with_clues(vec![ClueValue::Exact(1), ClueValue::Range(3, 1)]),
+                Err(SolverError::InvalidClueRange { min: 3, max: 1 })
+            );
+            assert!(Constraint::try_with_clues(vec![ClueValue::Exact(1), ClueValue::Range(1, 3)]).is_ok());
+        }
+
+        #[test]
+        fn test_num_arrangements() {
+            let constraint = Constraint::new(vec![2, 1]);
+
+            assert_eq!(constraint.num_arrangements(6), Line::generate_initial_candidates(6, &constraint, 1).len());
+            assert_eq!(Constraint::new(vec![]).num_arrangements(4), 1);
+            assert_eq!(Constraint::new(vec![3, 3]).num_arrangements(4), 0);
+        }
+
+        #[test]
+        fn test_fits() {
+            assert!(Constraint::new(vec![]).fits(0));
+            assert!(Constraint::new(vec![2, 1]).fits(4));
+            assert!(!Constraint::new(vec![2, 2]).fits(4));
+            assert!(Constraint::with_clues(vec![ClueValue::Range(1, 5), ClueValue::Exact(2)]).fits(4));
+        }
+
+        #[test]
+        fn test_filter() {
+            let values = vec![1, 2];
+            let constraint = Constraint::new(values);
+            
+            let ok_line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
+            let nok_line = Line::new(vec![CellState::Full, CellState::Full, CellState::Empty, CellState::Full]);
+            let lines = vec![ok_line.clone(), nok_line.clone()];
+
+            let filtered_lines = constraint.filter(&lines);
+
+            assert_eq!(filtered_lines.len(), 1);
+            assert!(filtered_lines.contains(&ok_line));
+            assert!(!filtered_lines.contains(&nok_line))
+        }
+    }
+
+    mod constraints {
+        use super::*;
+
+        #[test]
+        fn test_infer_dimensions() {
+            // █░█
+            // ░██
+            let rows = vec![Constraint::new(vec![1, 1]), Constraint::new(vec![2])];
+            let cols = vec![Constraint::new(vec![1]), Constraint::new(vec![1]), Constraint::new(vec![2])];
+            assert_eq!(Constraints::new(cols.clone(), rows.clone()).infer_dimensions(), Ok(Dimensions::new(2, 3)));
+
+            // Two rows can't hold a column of 1 and 1
+            let cols = vec![Constraint::new(vec![1]), Constraint::new(vec![1, 1]), Constraint::new(vec![1])];
+            assert_eq!(
+                Constraints::new(cols, rows).infer_dimensions(),
+                Err(SolverError::Infeasible { index: 1, axis: Axis::Col })
+            );
+        }
+
+        #[test]
+        fn test_duplicates() {
+            let row_constraints = vec![
+                Constraint::new(vec![1, 2]),
+                Constraint::new(vec![3]),
+                Constraint::new(vec![1, 2]),
+                Constraint::new(vec![]),
+                Constraint::new(vec![3]),
+                Constraint::new(vec![1, 2])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![1]),
+                Constraint::new(vec![2]),
+                Constraint::new(vec![1, 1])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            assert_eq!(constraints.duplicate_rows(), vec![vec![0, 2, 5], vec![1, 4]]);
+            assert!(constraints.duplicate_cols().is_empty());
+        }
+
+        #[test]
+        fn test_symmetries() {
+            let symmetries = |art: &str| crate::formats::art::parse(art).0.symmetries();
+
+            assert_eq!(symmetries("#.#\n.#.\n#.#\n"), vec![Symmetry::LeftRight, Symmetry::TopBottom, Symmetry::HalfTurn]);
+            assert_eq!(symmetries("##.##\n#...#\n"), vec![Symmetry::LeftRight]);
+            assert_eq!(symmetries("##.\n#..\n##.\n"), vec![Symmetry::TopBottom]);
+            assert_eq!(symmetries("#.##\n....\n##.#\n"), vec![Symmetry::HalfTurn]);
+            assert_eq!(symmetries("##.\n#..\n...\n"), vec![]);
+        }
+
+        #[test]
+        fn test_is_solvable_and_unique() {
+            let (unique, unique_dims) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let (ambiguous, ambiguous_dims) = crate::formats::art::parse("#.\n.#\n");
+            // Both rows need their middle cell empty, the middle column needs it full
+            let unsolvable = Constraints::new(
+                vec![Constraint::new(vec![1]), Constraint::new(vec![2]), Constraint::new(vec![1])],
+                vec![Constraint::new(vec![1, 1]), Constraint::new(vec![1, 1])]
+            );
+            let unsolvable_dims = Dimensions::new(2, 3);
+
+            assert!(unique.is_solvable(&unique_dims) && unique.is_unique(&unique_dims));
+            assert!(ambiguous.is_solvable(&ambiguous_dims) && !ambiguous.is_unique(&ambiguous_dims));
+            assert!(!unsolvable.is_solvable(&unsolvable_dims) && !unsolvable.is_unique(&unsolvable_dims));
+            // The wrong size is rejected without building a board
+            assert!(!unique.is_solvable(&ambiguous_dims));
+        }
+
+        #[test]
+        fn test_check_totals() {
+            let row_constraints = vec![Constraint::new(vec![1, 1]), Constraint::new(vec![2])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![2]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+            assert_eq!(constraints.check_totals(), Ok(()));
+
+            let row_constraints = vec![Constraint::new(vec![1, 1]), Constraint::new(vec![3])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![2]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+            let expected = SolverError::ClueTotalMismatch { row_total: 5, col_total: 4 };
+            assert_eq!(constraints.check_totals(), Err(expected.clone()));
+            assert_eq!(Board::try_new(constraints.clone(), Dimensions::new(2, 3)).err(), Some(expected.clone()));
+
+            let board = Board::new(constraints, Dimensions::new(2, 3));
+            assert_eq!(board.stats().passes, 0);
+            assert_eq!(board.validate(&board.grid()), Err(expected));
+
+            let ranged = vec![Constraint::with_clues(vec![ClueValue::Range(1, 3)]), Constraint::new(vec![1])];
+            let col_constraints = vec![Constraint::new(vec![1, 1]), Constraint::new(vec![1])];
+            assert_eq!(Constraints::new(col_constraints, ranged).check_totals(), Ok(()));
+        }
+
+        #[test]
+        fn test_parse_csv() {
+            // Lines legitimately differ in their number of blocks
+            let constraints = parse_csv("2,1\n3\n0\n", "1,1,1\n2\n").unwrap();
+
+            assert_eq!(constraints, Constraints::new(
+                vec![Constraint::new(vec![1, 1, 1]), Constraint::new(vec![2])],
+                vec![Constraint::new(vec![2, 1]), Constraint::new(vec![3]), Constraint::new(vec![])]
+            ));
+            assert!(parse_csv("2,1\n3\n", "1,a\n").is_err());
+        }
+
+        #[test]
+        fn test_to_clue_text() {
+            let (constraints, _) = crate::formats::art::parse("##.##\n#####\n.....\n#...#\n");
+
+            assert_eq!(
+                constraints.to_clue_text(),
+                "Row 1: 2 2 / Row 2: 5 / Row 3: (empty) / Row 4: 1 1 / \
+                 Column 1: 2 1 / Column 2: 2 / Column 3: 1 / Column 4: 2 / Column 5: 2 1"
+            );
+        }
+
+        #[test]
+        fn test_matches() {
+            let row_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![2])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![2]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            assert_eq!(constraints.num_rows(), 2);
+            assert_eq!(constraints.num_cols(), 3);
+            assert!(constraints.matches(&Dimensions::new(2, 3)));
+            assert!(!constraints.matches(&Dimensions::new(3, 2)));
+            assert!(!constraints.matches(&Dimensions::new(2, 4)));
+        }
+    }
+
+    mod puzzle {
+        use super::*;
+
+        #[test]
+        fn test_new_and_solve() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let puzzle = Puzzle::new(constraints.clone());
+            assert_eq!(puzzle.dimensions(), dimensions);
+            assert_eq!(puzzle, Puzzle::from_clues(&[vec![2], vec![2], vec![1, 1]], &[vec![1, 1], vec![2], vec![2]]));
+
+            let result = puzzle.solve();
+            let mut board = Board::new(constraints, dimensions);
+            assert_eq!(result.outcome, board.solve());
+            assert_eq!(result.grid, board.grid());
+            assert_eq!(result.stats, *board.stats());
+            assert_eq!(result.outcome, SolveOutcome::Solved);
+        }
+
+        #[test]
+        fn test_solve_any() {
+            let (constraints, _) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let logic = Puzzle::new(constraints).solve_any();
+            assert_eq!(logic.outcome, SolveOutcome::Solved);
+            assert!(!logic.stats.used_search);
+
+            // Propagation stalls on it, see `Board::from_solution_with_noise`
+            let (constraints, _) = crate::formats::art::parse("#..#\n.#.#\n.#..\n#..#\n");
+            let guessed = Puzzle::new(constraints.clone()).solve_any();
+            assert_eq!(guessed.outcome, SolveOutcome::Solved);
+            assert!(guessed.stats.used_search);
+            assert!(!Puzzle::new(constraints).solve().stats.used_search);
+        }
+
+        #[test]
+        fn test_non_square() {
+            let puzzle = Puzzle::from_clues(&[vec![3], vec![1]], &[vec![1], vec![2], vec![1]]);
+            assert_eq!((puzzle.dimensions().num_rows(), puzzle.dimensions().num_cols()), (2, 3));
+
+            let result = puzzle.solve();
+            assert_eq!(result.outcome, SolveOutcome::Solved);
+            assert_eq!(result.grid[1], vec![CellState::Empty, CellState::Full, CellState::Empty]);
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_serde() {
+            let puzzle = Puzzle::new(Constraints::new(
+                vec![Constraint::new(vec![1]), Constraint::with_clues(vec![ClueValue::Range(1, 2)])],
+                vec![Constraint::new(vec![2]), Constraint::new(vec![])]
+            ));
+
+            let json = serde_json::to_string(&puzzle).unwrap();
+            assert_eq!(json, r#"{"cols":[[1],[[1,2]]],"rows":[[2],[]]}"#);
+            assert_eq!(serde_json::from_str::<Puzzle>(&json).unwrap(), puzzle);
+            assert!(serde_json::from_str::<Puzzle>(r#"{"cols":[[1],[[3,1]]],"rows":[[2],[]]}"#).is_err());
+            assert!(serde_json::from_str::<Puzzle>(r#"{"cols":[[1],[[0,2]]],"rows":[[2],[]]}"#).is_err());
+        }
+    }
+
+    mod line {
+        use super::*;
+
+        #[test]
+        fn test_to_constraint() {
+            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
+            let constraint = Constraint::new(vec![1, 2]);
+
+            assert_eq!(line.to_constraint(), constraint)
+        }
+
+        #[test]
+        fn test_enumerate_lines() {
+            let lines = Line::enumerate_lines(4, &Constraint::new(vec![2]));
+            let ranged = Line::enumerate_lines(4, &Constraint::with_clues(vec![ClueValue::Range(1, 2)]));
+
+            let (full, empty) = (CellState::Full, CellState::Empty);
+            assert_eq!(lines.len(), 3);
+            assert_eq!(lines.first().unwrap().cells(), [full, full, empty, empty]);
+            assert_eq!(lines.last().unwrap().cells(), [empty, empty, full, full]);
+            // Ranged clues are interleaved by position rather than grouped by block size
+            assert_eq!(ranged.len(), 7);
+            assert_eq!(ranged[1].cells(), [full, full, empty, empty]);
+            assert_eq!(ranged.last().unwrap().cells(), [empty, empty, empty, full]);
+        }
+
+        #[test]
+        fn test_is_complete() {
+            let complete = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full]);
+            let unknown = Line::new(vec![CellState::Full, CellState::Unknown, CellState::Full]);
+            let invalid = Line::new(vec![CellState::Full, CellState::Invalid, CellState::Full]);
+
+            assert!(complete.is_complete());
+            assert!(!unknown.is_complete());
+            assert!(!invalid.is_complete());
+        }
+
+        #[test]
+        fn test_is_valid_for() {
+            let constraint = Constraint::new(vec![1, 2]);
+            let matching = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
+            let non_matching = Line::new(vec![CellState::Full, CellState::Full, CellState::Empty, CellState::Full]);
+            let incomplete = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Unknown]);
+
+            assert!(matching.is_valid_for(&constraint));
+            assert!(!non_matching.is_valid_for(&constraint));
+            assert!(!incomplete.is_valid_for(&constraint));
+        }
+
+        #[test]
+        fn test_equivalent() {
+            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
+            let equiv_line= Line::new(vec![CellState::Full, CellState::Empty, CellState::Unknown, CellState::Unknown]);
+            let nequiv_line = Line::new(vec![CellState::Unknown, CellState::Full, CellState::Unknown, CellState::Unknown]);
+
+            assert!(line.equivalient(&equiv_line));
+            assert!(!line.equivalient(&nequiv_line));
+        }
+
+        #[test]
+        fn test_filter() {
+            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
+            let equiv_line= Line::new(vec![CellState::Full, CellState::Empty, CellState::Unknown, CellState::Unknown]);
+            let nequiv_line = Line::new(vec![CellState::Unknown, CellState::Full, CellState::Unknown, CellState::Unknown]);            
+            let line_candidates = vec![line.clone(), equiv_line.clone(), nequiv_line.clone()];
+
+            let filtered_lines = line.filter(&line_candidates);
+
+            assert_eq!(filtered_lines.len(), 2);
+            assert!(filtered_lines.contains(&line));
+            assert!(filtered_lines.contains(&equiv_line));
+            assert!(!filtered_lines.contains(&nequiv_line));
+        }
+
+        #[test]
+        fn test_bitmask_backend_matches_cells_backend() {
+            let mut state: u64 = 0x1234_5678_9abc_def1;
+            let mut next_cell = || [CellState::Full, CellState::Empty, CellState::Unknown][(xorshift(&mut state) % 3) as usize];
+
+            for width in [1, 64, 128] {
+                for _ in 0..50 {
+                    let first = (0..width).map(|_| next_cell()).collect::<Vec<CellState>>();
+                    let second = (0..width).map(|_| next_cell()).collect::<Vec<CellState>>();
+                    let (bits, other_bits) = (Line::new(first.clone()), Line::new(second.clone()));
+                    let (cells, other_cells) = (Line::with_cells_backend(first.clone()), Line::with_cells_backend(second));
+                    assert!(bits.bits().is_some() && cells.bits().is_none());
+
+                    assert_eq!(bits, cells);
+                    assert_eq!(bits.cells(), first);
+                    assert_eq!((&bits & &other_bits).cells(), (&cells & &other_cells).cells());
+                    assert_eq!((&bits | &other_bits).cells(), (&cells | &other_cells).cells());
+                    assert_eq!(bits.equivalient(&other_bits), cells.equivalient(&other_cells));
+                    assert_eq!(bits.agrees_with(&other_bits), cells.agrees_with(&other_cells));
+                    assert_eq!(bits.is_complete(), cells.is_complete());
+                    assert_eq!(bits.has_known_cells(), cells.has_known_cells());
+                    assert_eq!(bits.to_constraint(), cells.to_constraint());
+
+                    let complete = bits.fill_unknown(CellState::Empty);
+                    let sum = Line::sum(&[complete.clone(), bits.fill_unknown(CellState::Full)]).unwrap();
+                    let cells_sum = Line::sum(&[Line::with_cells_backend(complete.cells()), cells.fill_unknown(CellState::Full)]).unwrap();
+                    assert_eq!(sum.cells(), cells_sum.cells());
+                    assert_eq!(sum, bits);
+                }
+            }
+
+            // Lines that don't fit in the masks, or that hold an `Invalid` cell, use the `Vec`
+            assert!(Line::unknown(129).bits().is_none());
+            assert!(Line::new(vec![CellState::Invalid]).bits().is_none());
+            let arrangements = Line::generate_initial_candidates(128, &Constraint::new(vec![60, 60]), 1);
+            assert_eq!(arrangements.len(), 36);
+            assert!(arrangements.iter().all(|line| line.bits().is_some() && line.to_constraint() == Constraint::new(vec![60, 60])));
+        }
+
+        #[test]
+        fn test_agrees_with_matches_equivalent() {
+            let full = CellState::Full;
+            let empty = CellState::Empty;
+            let unknown = CellState::Unknown;
+            let invalid = CellState::Invalid;
+            let candidates = Line::generate_initial_candidates(5, &Constraint::new(vec![2, 1]), 1);
+            let currents = [
+                Line::unknown(5),
+                Line::new(vec![unknown, full, unknown, unknown, empty]),
+                Line::new(vec![empty, unknown, unknown, full, unknown]),
+                Line::new(vec![unknown, invalid, unknown, unknown, unknown])
+            ];
+
+            for current in &currents {
+                for candidate in &candidates {
+                    assert_eq!(candidate.agrees_with(current), candidate.equivalient(current));
+                }
+            }
+        }
+
+        #[test]
+        fn test_fill_unknown() {
+            let full = CellState::Full;
+            let empty = CellState::Empty;
+            let unknown = CellState::Unknown;
+            let line = Line::new(vec![unknown, full, unknown, full, empty]);
+
+            assert_eq!(line.fill_unknown(empty), Line::new(vec![empty, full, empty, full, empty]));
+            assert_eq!(line.cells()[0], unknown);
+        }
+
+        #[test]
+        fn test_min_gap_candidates() {
+            let candidates = Line::generate_initial_candidates(6, &Constraint::new(vec![1, 2]), 2);
+
+            let (full, empty) = (CellState::Full, CellState::Empty);
+            assert_eq!(candidates, vec![
+                Line::new(vec![full, empty, empty, full, full, empty]),
+                Line::new(vec![full, empty, empty, empty, full, full]),
+                Line::new(vec![empty, full, empty, empty, full, full])
+            ]);
+            for candidate in &candidates {
+                let starts = candidate.block_starts();
+                // The first block is a single cell, so two empty cells follow it
+                assert!(starts[1] - starts[0] >= 3);
+            }
+            assert!(Line::generate_initial_candidates(5, &Constraint::new(vec![1, 2]), 3).is_empty());
+        }
+
+        #[test]
+        fn test_min_gap_checks() {
+            // The columns write #.# before any candidate is checked, but with two empty cells
+            // between blocks the row can't be arranged in three cells
+            let row_constraints = vec![Constraint::new(vec![1, 1])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+            let config = SolveConfig { min_gap: 2, ..SolveConfig::default() };
+
+            let mut board = Board::with_config(constraints.clone(), Dimensions::new(1, 3), config.clone());
+            assert_eq!(board.solve(), SolveOutcome::Contradiction { index: 0, axis: Axis::Row });
+            assert_eq!(
+                board.validate(&board.grid()),
+                Err(SolverError::LineMismatch { index: 0, axis: Axis::Row })
+            );
+
+            let mut board = Board::new(constraints, Dimensions::new(1, 3));
+            assert_eq!(board.solve(), SolveOutcome::Solved);
+        }
+
+        #[test]
+        fn test_ranged_clue_candidates() {
+            let constraint = Constraint::with_clues(vec![ClueValue::Range(1, 2)]);
+            let line = |s: &str| {
+                Line::new(s
+                    .chars()
+                    .map(|c| if c == '#' { CellState::Full } else { CellState::Empty })
+                    .collect())
+            };
+
+            let candidates = Line::generate_initial_candidates(3, &constraint, 1);
+            let expected = [line("#.."), line(".#."), line("..#"), line("##."), line(".##")];
+
+            assert_eq!(candidates.len(), expected.len());
+            assert!(expected.iter().all(|line| candidates.contains(line)));
+            assert_eq!(constraint.num_arrangements(3), expected.len());
+            assert!(line("##.").is_valid_for(&constraint));
+            assert!(!line("###").is_valid_for(&constraint));
+        }
+
+        #[test]
+        fn test_ranged_clue_is_union_of_sizes() {
+            let constraint = Constraint::with_clues(vec![ClueValue::from(1), ClueValue::try_from(2..=4).unwrap()]);
+            assert_eq!(ClueValue::try_from(RangeInclusive::new(4, 2)), Err(SolverError::InvalidClueRange { min: 4, max: 2 }));
+
+            let mut union = (2..=4)
+                .flat_map(|size| Line::generate_initial_candidates(8, &Constraint::new(vec![1, size]), 1))
+                .map(|line| line.to_compact_string())
+                .collect::<Vec<String>>();
+            let mut candidates = Line::generate_initial_candidates(8, &constraint, 1)
+                .iter()
+                .map(Line::to_compact_string)
+                .collect::<Vec<String>>();
+            union.sort();
+            candidates.sort();
+
+            assert_eq!(candidates, union);
+        }
+
+        #[test]
+        fn test_streamed_sum() {
+            let constraint = Constraint::new(vec![2, 1]);
+            let current = Line::new(vec![CellState::Unknown, CellState::Full, CellState::Unknown, CellState::Unknown, CellState::Unknown]);
+
+            let candidates = current.filter(&Line::generate_initial_candidates(5, &constraint, 1));
+            let stored = Line::sum(&candidates);
+            let streamed = Line::streamed_sum(5, &constraint, &current, 1);
+
+            assert_eq!(streamed, stored);
+            assert_eq!(Line::streamed_sum(3, &Constraint::new(vec![2, 2]), &Line::unknown(3), 1), None);
+        }
+
+        #[test]
+        fn test_print() {
+            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
+            println!("{}", &line)
+        }
+
+        #[test]
+        fn test_and() {
+            let a = Line::new(vec![CellState::Empty, CellState::Empty, CellState::Empty, CellState::Full, CellState::Full, CellState::Full]);
+            let b = Line::new(vec![CellState::Empty, CellState::Full, CellState::Unknown, CellState::Empty, CellState::Full, CellState::Unknown]);
+            let a_and_b = Line::new(vec![CellState::Empty, CellState::Unknown, CellState::Unknown, CellState::Unknown, CellState::Full, CellState::Unknown]);
+
+            let res = &a & &b;
+            assert_eq!(res, a_and_b)    
+        }
+        
+        #[test]
+        fn test_or() {
+            let a = Line::new(vec![CellState::Empty, CellState::Empty, CellState::Empty, CellState::Full, CellState::Full, CellState::Full]);
+            let b = Line::new(vec![CellState::Empty, CellState::Full, CellState::Unknown, CellState::Empty, CellState::Full, CellState::Unknown]);
+            let a_or_b = Line::new(vec![CellState::Empty, CellState::Invalid, CellState::Empty, CellState::Invalid, CellState::Full, CellState::Full]);
+
+            let res = &a | &b;
+            assert_eq!(res, a_or_b)    
+        }
+
+        #[test]
+        fn test_sum() {
+            let a = Line::new(vec![CellState::Empty, CellState::Empty, CellState::Empty, CellState::Full, CellState::Full, CellState::Full]);
+            let b = Line::new(vec![CellState::Empty, CellState::Full, CellState::Unknown, CellState::Empty, CellState::Full, CellState::Unknown]);
+            let a_and_b = Line::new(vec![CellState::Empty, CellState::Unknown, CellState::Unknown, CellState::Unknown, CellState::Full, CellState::Unknown]);
+
+            let res = Line::sum(&[a, b]).unwrap();
+            assert_eq!(res, a_and_b)    
+        }
+    }
+
+    mod line_techniques {
+        use super::*;
+
+        fn line(s: &str) -> Line {
+            let cells = s.chars()
+                .map(|c| match c {
+                    '#' => CellState::Full,
+                    '.' => CellState::Empty,
+                    _ => CellState::Unknown
+                })
+                .collect();
+            Line::new(cells)
+        }
+
+        #[test]
+        fn test_overlap_single_block() {
+            let forced = Line::forced_given(&Constraint::new(vec![8]), &line("??????????"));
+            assert_eq!(forced, Some(line("??######??")));
+        }
+
+        #[test]
+        fn test_overlap_two_blocks() {
+            let forced = Line::forced_given(&Constraint::new(vec![4, 3]), &line("??????????"));
+            assert_eq!(forced, Some(line("??##???#??")));
+        }
+
+        #[test]
+        fn test_known_empty_at_start() {
+            let forced = Line::forced_given(&Constraint::new(vec![4, 3]), &line(".?????????"));
+            assert_eq!(forced, Some(line(".?###??##?")));
+        }
+
+        #[test]
+        fn test_known_full_completes_line() {
+            let forced = Line::forced_given(&Constraint::new(vec![1]), &line("????#"));
+            assert_eq!(forced, Some(line("....#")));
+        }
+
+        #[test]
+        fn test_known_cells_pin_blocks() {
+            let forced = Line::forced_given(&Constraint::new(vec![2, 2]), &line("?#???#?"));
+            assert_eq!(forced, Some(line("?#?.?#?")));
+
+            let forced = Line::forced_given(&Constraint::new(vec![2, 2]), &line("##???#?"));
+            assert_eq!(forced, Some(line("##..?#?")));
+        }
+
+        #[test]
+        fn test_contradiction() {
+            let forced = Line::forced_given(&Constraint::new(vec![3]), &line("#.#??"));
+            assert_eq!(forced, None);
+        }
+    }
+
+    mod board {
+        use super::*;
+
+        #[test]
+        fn test_is_solved() {
+            let dimensions = Dimensions::new(2, 4);
+            
+            let row_constraints = vec![
+                Constraint::new(vec![1, 2]),
+                Constraint::new(vec![1, 2])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![2]),
+                Constraint::new(vec![]),
+                Constraint::new(vec![2]),
+                Constraint::new(vec![2])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+            
+            let _board = Board::new(constraints, dimensions);
+
+            // TODO: Implement this
+        }
+
+        #[test]
+        fn test_trivial_lines_solved_on_construction() {
+            let dimensions = Dimensions::new(3, 3);
+
+            let row_constraints = vec![
+                Constraint::new(vec![]),
+                Constraint::new(vec![3]),
+                Constraint::new(vec![1])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![1]),
+                Constraint::new(vec![2]),
+                Constraint::new(vec![1])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let board = Board::new(constraints, dimensions);
+
+            assert_eq!(board.to_line(0, Axis::Row), Line::empty(3));
+            assert_eq!(board.to_line(1, Axis::Row), Line::full(3));
+            assert_eq!(board.to_line(2, Axis::Row), Line::unknown(3));
+            assert_eq!(board.row_candidates[0].len(), 1);
+            assert_eq!(board.row_candidates[1].len(), 1);
+        }
+
+        #[test]
+        fn test_min_passes_estimate() {
+            // Every row has a single arrangement
+            let (constraints, dimensions) = crate::formats::art::parse("#.#\n...\n###\n");
+            let mut board = Board::new(constraints, dimensions);
+            assert_eq!(board.min_passes_estimate(), 1);
+            board.solve();
+            assert!(board.stats().passes >= 1);
+            assert_eq!(board.min_passes_estimate(), 0);
+
+            let (constraints, dimensions) = crate::formats::art::parse("..#..\n.###.\n#####\n.#.#.\n#...#\n");
+            let mut board = Board::new(constraints, dimensions);
+            assert_eq!(board.min_passes_estimate(), 2);
+            board.solve();
+            assert!(board.stats().passes >= 2);
+
+            let (constraints, dimensions) = crate::formats::art::parse("#...\n.#..\n..#.\n...#\n");
+            let mut board = Board::new(constraints, dimensions);
+            assert_eq!(board.min_passes_estimate(), 2);
+            board.solve();
+            assert!(board.stats().passes >= 2);
+        }
+
+        #[test]
+        fn test_solve_any() {
+            // Two solutions, propagation alone makes no progress
+            let dimensions = Dimensions::new(2, 2);
+            let row_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints, dimensions);
+            let grid = board.solve_any().unwrap();
+            assert_eq!(board.validate(&grid), Ok(()));
+
+            let (constraints, dimensions) = crate::formats::art::parse("#...\n.#..\n..#.\n...#\n");
+            let mut board = Board::new(constraints, dimensions);
+            let grid = board.solve_any().unwrap();
+            assert_eq!(board.validate(&grid), Ok(()));
+
+            let mut board = Board::with_config(board.constraints(), dimensions, SolveConfig { streaming: true, ..SolveConfig::default() });
+            let grid = board.solve_any().unwrap();
+            assert_eq!(board.validate(&grid), Ok(()));
+
+            // Both rows are full but each column only has room for one cell
+            let row_constraints = vec![Constraint::new(vec![2]), Constraint::new(vec![2])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints, Dimensions::new(2, 2));
+            assert_eq!(board.solve_any(), None);
+        }
+
+        #[test]
+        fn test_weighted_guesses() {
+            // Rows [1] in a line of three: each cell is full in a third of the candidates
+            let (constraints, dimensions) = crate::formats::art::parse("#..\n.#.\n..#\n");
+            let board = Board::new(constraints, dimensions);
+            assert!(board.full_likelihood(0) < 0.5);
+
+            let (constraints, dimensions) = crate::formats::art::parse(".#.\n###\n.#.\n");
+            let board = Board::new(constraints, dimensions);
+            assert!(board.full_likelihood(1) > 0.5);
+
+            let art = "..###\n#....\n#...#\n.#.##\n..##.\n";
+            let (constraints, dimensions) = crate::formats::art::parse(art);
+            let config = SolveConfig { weighted_guesses: false, ..SolveConfig::default() };
+            let mut naive = Board::with_config(constraints.clone(), dimensions, config);
+            let mut weighted = Board::new(constraints, dimensions);
+
+            let grid = weighted.solve_any().unwrap();
+            assert_eq!(weighted.validate(&grid), Ok(()));
+            naive.solve_any().unwrap();
+            assert!(weighted.stats().backtracks < naive.stats().backtracks);
+        }
+
+        #[test]
+        fn test_symmetry_pruning() {
+            // Mirrored left to right, and propagation alone stalls on it
+            let art = "#..#..#\n.##.##.\n..#.#..\n#.....#\n...#...\n.##.##.\n.##.##.\n";
+            let (constraints, dimensions) = crate::formats::art::parse(art);
+            assert_eq!(constraints.symmetries(), vec![Symmetry::LeftRight]);
+
+            let config = SolveConfig { symmetry_pruning: true, ..SolveConfig::default() };
+            let mut plain = Board::new(constraints.clone(), dimensions);
+            let mut pruned = Board::with_config(constraints, dimensions, config);
+
+            let grid = pruned.solve_any().unwrap();
+            assert_eq!(pruned.validate(&grid), Ok(()));
+            plain.solve_any().unwrap();
+            assert_eq!(plain.stats().backtracks, 32);
+            assert_eq!(pruned.stats().backtracks, 15);
+        }
+
+        #[test]
+        fn test_solved_or_err() {
+            let (constraints, dimensions) = crate::formats::art::parse("#.#\n.##\n");
+            let mut board = Board::new(constraints, dimensions);
+            assert_eq!(board.solved_or_err(), Ok(vec![vec![true, false, true], vec![false, true, true]]));
+            assert!(board.is_solved());
+
+            let row_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let mut board = Board::new(Constraints::new(col_constraints, row_constraints), Dimensions::new(2, 2));
+            assert_eq!(board.solved_or_err(), Err(SolverError::Ambiguous));
+
+            // Row 0 needs a full cell in the last column, whose clue is empty
+            let row_constraints = vec![Constraint::new(vec![1, 1]), Constraint::new(vec![1])];
+            let col_constraints = vec![Constraint::new(vec![2]), Constraint::new(vec![1]), Constraint::new(vec![])];
+            let mut board = Board::new(Constraints::new(col_constraints, row_constraints), Dimensions::new(2, 3));
+            assert_eq!(board.solved_or_err(), Err(SolverError::Contradiction));
+
+            let row_constraints = vec![Constraint::new(vec![3]), Constraint::new(vec![])];
+            let col_constraints = vec![Constraint::new(vec![2]), Constraint::new(vec![1])];
+            let mut board = Board::new(Constraints::new(col_constraints, row_constraints), Dimensions::new(2, 2));
+            assert_eq!(board.solved_or_err(), Err(SolverError::Infeasible { index: 0, axis: Axis::Row }));
+
+            let row_constraints = vec![Constraint::new(vec![2]), Constraint::new(vec![2])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let mut board = Board::new(Constraints::new(col_constraints, row_constraints), Dimensions::new(2, 2));
+            assert_eq!(board.solved_or_err(), Err(SolverError::ClueTotalMismatch { row_total: 4, col_total: 2 }));
+        }
+
+        /// Every grid whose rows match their clues, kept if the columns match as well.
+        fn brute_force_solve(board: &Board) -> Vec<Vec<Vec<CellState>>> {
+            board.row_constraints
+                .iter()
+                .map(|constraint| Line::generate_initial_candidates(board.dimensions.num_cols, constraint, 1))
+                .multi_cartesian_product()
+                .map(|rows| rows.into_iter().map(|row| row.cells()).collect::<Vec<Vec<CellState>>>())
+                .filter(|grid| board.validate(grid).is_ok())
+                .collect()
+        }
+
+        /// A random grid of up to `max_rows` by `max_cols` cells as art, drawn from `state` so the
+        /// same puzzles are checked on every run.
+        fn random_art(state: &mut u64, max_rows: u64, max_cols: u64) -> String {
+            let num_rows = 1 + xorshift(state) % max_rows;
+            let num_cols = 1 + xorshift(state) % max_cols;
+            (0..num_rows)
+                .map(|_| (0..num_cols).map(|_| if xorshift(state).is_multiple_of(2) { '#' } else { '.' }).collect::<String>())
+                .join("\n")
+        }
+
+        #[test]
+        fn test_matches_brute_force() {
+            let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+
+            for _ in 0..200 {
+                let art = random_art(&mut state, 5, 6);
+                let (constraints, dimensions) = crate::formats::art::parse(&art);
+
+                let mut board = Board::new(constraints, dimensions);
+                let mut expected = brute_force_solve(&board);
+                assert!(!expected.is_empty(), "{}", art);
+
+                board.solve();
+                let mut solutions = Vec::new();
+                board.clone().search_solutions(usize::MAX, &mut solutions);
+
+                expected.sort_by_key(|grid| format!("{:?}", grid));
+                solutions.sort_by_key(|grid| format!("{:?}", grid));
+                assert_eq!(solutions, expected, "{}", art);
+            }
+        }
+
+        #[test]
+        fn test_solve_is_idempotent() {
+            let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+
+            for _ in 0..300 {
+                let art = random_art(&mut state, 8, 8);
+                let (constraints, dimensions) = crate::formats::art::parse(&art);
+
+                let mut board = Board::new(constraints, dimensions);
+                let outcome = board.solve();
+                let solved = board.clone();
+
+                // Solving again never changes the grid, and a solved board is left alone entirely
+                assert_eq!(board.solve(), outcome, "{}", art);
+                assert_eq!(board.grid(), solved.grid(), "{}", art);
+                if outcome == SolveOutcome::Solved {
+                    assert_eq!(board.stats(), solved.stats(), "{}", art);
+                    assert_eq!(board.num_candidates(), solved.num_candidates(), "{}", art);
+                }
+            }
+        }
+
+        #[test]
+        fn test_solve_into() {
+            let (constraints, dimensions) = crate::formats::art::parse("#.#\n.##\n");
+
+            let mut board = Board::new(constraints.clone(), dimensions);
+            let mut out = [0; 6];
+            assert_eq!(board.solve_into(&mut out), Ok(()));
+            assert_eq!(out, [2, 1, 2, 1, 2, 2]);
+
+            let mut board = Board::new(constraints, dimensions);
+            let mut out = [0; 5];
+            assert_eq!(board.solve_into(&mut out), Err(SolverError::BufferTooSmall { required: 6, actual: 5 }));
+            assert_eq!(board.stats().passes, 0);
+        }
+
+        #[test]
+        fn test_ascii_art_scale() {
+            let (constraints, dimensions) = crate::formats::art::parse("#.#\n.##\n");
+            let mut board = Board::new(constraints, dimensions);
+            board.solve();
+
+            for scale in [1, 2, 3] {
+                let art = board.to_ascii_art(scale);
+                let lines = art.lines().collect::<Vec<&str>>();
+                assert_eq!(lines.len(), 2);
+                assert!(lines.iter().all(|line| line.chars().count() == 3 * scale));
+            }
+            assert_eq!(board.to_ascii_art(2), "██░░██\n░░████\n");
+        }
+
+        #[test]
+        fn test_axis_accessors() {
+            assert_eq!(Axis::Row.other(), Axis::Col);
+            assert_eq!(Axis::Col.other(), Axis::Row);
+
+            // Two rows of three cells
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.#.\n");
+            let board = Board::new(constraints, dimensions);
+
+            assert_eq!(board.clues(Axis::Row), [Constraint::new(vec![2]), Constraint::new(vec![1])]);
+            assert_eq!(board.clues(Axis::Col), [Constraint::new(vec![1]), Constraint::new(vec![2]), Constraint::new(vec![])]);
+            assert_eq!(board.candidates(Axis::Row, 0).len(), 2);
+            assert!(board.candidates(Axis::Row, 0).iter().all(|line| line.cells().len() == 3));
+            assert_eq!(board.candidates(Axis::Col, 1), [Line::full(2)]);
+            assert_eq!(board.constraint_for(0, Axis::Row), &Constraint::new(vec![2]));
+            assert_eq!(board.constraint_for(2, Axis::Col), &Constraint::new(vec![]));
+            assert_eq!(board.to_line(2, Axis::Col), Line::empty(2));
+
+            assert_eq!(SolverError::LineMismatch { index: 1, axis: Axis::Row }.to_string(), "Row 1 does not match its clue");
+            assert_eq!(SolverError::LineNotForced { index: 0, axis: Axis::Col }.to_string(), "Column 0 is not yet determined");
+        }
+
+        #[test]
+        fn test_add_clue() {
+            let (constraints, dimensions) = crate::formats::art::parse(".#.\n###\n#.#\n");
+            let mut board = Board::unclued(dimensions, SolveConfig::default());
+            assert_eq!(board.solve(), SolveOutcome::Stalled);
+            assert_eq!(board.num_unknown(), 9);
+
+            let clues = (0..3)
+                .map(|idx| (Axis::Row, idx, constraints.rows[idx].clone()))
+                .chain((0..3).map(|idx| (Axis::Col, idx, constraints.cols[idx].clone())));
+            let mut determined = 0;
+            for (axis, idx, clue) in clues {
+                let newly_determined = board.add_clue(axis, idx, clue);
+                assert_eq!(9 - board.num_unknown(), determined + newly_determined);
+                determined += newly_determined;
+            }
+
+            assert!(board.is_solved());
+            assert_eq!(board.validate(&board.grid()), Ok(()));
+            assert_eq!(board.to_ascii_string(), "  []  \n[][][]\n[]  []\n");
+        }
+
+        #[test]
+        fn test_empty_board() {
+            let dimensions = Dimensions::new(4, 4);
+
+            let row_constraints = (0..4).map(|_| Constraint::new(vec![])).collect::<Vec<Constraint>>();
+            let col_constraints = (0..4).map(|_| Constraint::new(vec![])).collect::<Vec<Constraint>>();
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints, dimensions);
+
+            // Empty clues are trivial lines, written when the board is built
+            assert_eq!(board.grid(), vec![vec![CellState::Empty; 4]; 4]);
+            assert_eq!(board.solve(), SolveOutcome::Solved);
+            assert_eq!(board.stats().passes, 0);
+            assert_eq!(board.stats().candidates_generated, 8);
+        }
+
+        #[test]
+        fn test_puzzle_json_round_trip() {
+            let dimensions = Dimensions::new(2, 3);
+
+            let row_constraints = vec![
+                Constraint::new(vec![1, 1]),
+                Constraint::new(vec![2])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![1]),
+                Constraint::new(vec![1]),
+                Constraint::new(vec![2])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints.clone(), dimensions);
+            board.solve();
+            assert_eq!(board.constraints(), constraints);
+
+            let json = serde_json::from_str::<serde_json::Value>(&board.to_puzzle_json()).unwrap();
+            let to_constraints = |hints: &serde_json::Value| {
+                hints.as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|values| {
+                        let values = values.as_array()
+                            .unwrap()
+                            .iter()
+                            .map(|value| value.as_u64().unwrap() as usize)
+                            .collect();
+                        Constraint::new(values)
+                    })
+                    .collect::<Vec<Constraint>>()
+            };
+
+            let parsed = Constraints::new(to_constraints(&json["hintsY"]), to_constraints(&json["hintsX"]));
+            assert_eq!(parsed, constraints);
+            assert_eq!(json["solution"], serde_json::json!([[2, 1, 2], [1, 2, 2]]));
+        }
+
+        #[test]
+        fn test_solve_stops_when_rows_determined() {
+            let dimensions = Dimensions::new(3, 4);
+
+            let row_constraints = vec![
+                Constraint::new(vec![2, 1]),
+                Constraint::new(vec![1, 2]),
+                Constraint::new(vec![2, 1])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![3]),
+                Constraint::new(vec![1, 1]),
+                Constraint::new(vec![1]),
+                Constraint::new(vec![3])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints, dimensions);
+            board.solve();
+
+            assert!(board.is_solved());
+            assert_eq!(board.stats().passes, 1);
+            assert_eq!(board.stats().candidates_generated, 3 + 1 + 1 + 3 + 1);
+        }
+
+        #[test]
+        fn test_solve_continues_past_unproductive_pass() {
+            // The first row pass determines nothing new, only the columns can make progress
+            let dimensions = Dimensions::new(4, 4);
+
+            let row_constraints = vec![
+                Constraint::new(vec![4]),
+                Constraint::new(vec![2]),
+                Constraint::new(vec![1]),
+                Constraint::new(vec![1])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![1]),
+                Constraint::new(vec![1, 1]),
+                Constraint::new(vec![2, 1]),
+                Constraint::new(vec![2])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints, dimensions);
+            board.solve();
+
+            assert!(board.is_solved());
+            assert_eq!(board.to_string(), "████\n░░██\n░█░░\n░░█░\n\n");
+        }
+
+        #[test]
+        fn test_merge_axis_once() {
+            // The middle two cells of a [3] in four cells are full whatever the placement
+            let row_constraints = vec![Constraint::new(vec![3]), Constraint::new(vec![1])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1]), Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints, Dimensions::new(2, 4));
+            let num_unknown = board.num_unknown();
+            assert_eq!(board.merge_axis_once(Axis::Row), Ok(true));
+            assert_eq!(board.num_unknown(), num_unknown - 2);
+
+            // Each row's block can still sit in either of the first two cells
+            let row_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1]), Constraint::new(vec![])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let mut board = Board::new(constraints, Dimensions::new(2, 3));
+            assert_eq!(board.merge_axis_once(Axis::Row), Ok(false));
+            assert_eq!(board.merge_axis_once(Axis::Col), Ok(false));
+        }
+
+        #[test]
+        fn test_user_cell_contradiction() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n..#\n");
+
+            for streaming in [false, true] {
+                let config = SolveConfig { streaming, ..SolveConfig::default() };
+                let mut board = Board::with_config(constraints.clone(), dimensions, config);
+
+                // Row 0 is [2], which always covers the middle cell
+                board.set_cell(0, 1, CellState::Empty);
+                assert_eq!(board.solve(), SolveOutcome::Contradiction { index: 0, axis: Axis::Row });
+                // Nothing is written for the contradicting line
+                assert_eq!(board.to_line(0, Axis::Row), Line::new(vec![CellState::Unknown, CellState::Empty, CellState::Unknown]));
+            }
+        }
+
+        #[test]
+        fn test_rle() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let mut board = Board::new(constraints, dimensions);
+            board.solve();
+
+            let rle = board.to_rle();
+            assert_eq!(rle, "2#1.|1.2#|1#1.1#");
+            let decoded = Board::from_rle(&rle).unwrap();
+            assert_eq!(decoded.grid(), board.grid());
+            assert_eq!(decoded.constraints(), board.constraints());
+
+            // A single full cell in the middle of a 20x20 board
+            let sparse = (0..20)
+                .map(|row| {
+                    (0..20)
+                        .map(|col| if (row, col) == (10, 10) { CellState::Full } else { CellState::Empty })
+                        .collect()
+                })
+                .collect::<Vec<Vec<CellState>>>();
+            let (constraints, dimensions) = Board::clues_of(&sparse);
+            let mut board = Board::new(constraints, dimensions);
+            board.apply_solution(&sparse).unwrap();
+
+            let rle = board.to_rle();
+            // 19 rows of "20." plus "10.1#9." and the separators, against one character per cell
+            assert_eq!(rle.len(), 19 * 3 + 7 + 19);
+            assert!(rle.len() < 20 * 20);
+            assert_eq!(Board::from_rle(&rle).unwrap().grid(), sparse);
+
+            assert!(matches!(Board::from_rle("2#|1#"), Err(SolverError::InvalidRle(_))));
+            assert!(matches!(Board::from_rle("1?"), Err(SolverError::InvalidRle(_))));
+            assert!(matches!(Board::from_rle("1#2"), Err(SolverError::InvalidRle(_))));
+            assert!(matches!(Board::from_rle(""), Err(SolverError::InvalidRle(_))));
+            assert!(matches!(Board::from_rle("1#||1#"), Err(SolverError::InvalidRle(_))));
+            assert!(matches!(Board::from_rle("0#1."), Err(SolverError::InvalidRle(_))));
+
+            let max = crate::MAX_DIMENSION;
+            assert_eq!(Board::from_rle("99999999999#").err(), Some(SolverError::DimensionTooLarge { size: 99999999999, max }));
+            assert_eq!(
+                Board::from_rle(&format!("{}#1.", max)).err(),
+                Some(SolverError::DimensionTooLarge { size: max + 1, max })
+            );
+            assert_eq!(
+                Board::from_rle(&vec!["1#"; max + 1].join("|")).err(),
+                Some(SolverError::DimensionTooLarge { size: max + 1, max })
+            );
+            assert_eq!(Board::from_rle("1#").unwrap().to_rle(), "1#");
+        }
+
+        #[test]
+        fn test_clear_line() {
+            let (constraints, dimensions) = crate::formats::art::parse("##..\n.##.\n..##\n#..#\n");
+            let mut board = Board::new(constraints, dimensions);
+            let before = board.clone();
+
+            // The user fills in the second row, which narrows down the crossing columns
+            for (col, cell) in [CellState::Empty, CellState::Full, CellState::Full, CellState::Empty].into_iter().enumerate() {
+                board.set_cell(1, col, cell);
+            }
+            board.update_candidates(Axis::Row);
+            board.update_candidates(Axis::Col);
+            assert_ne!(board.candidates(Axis::Col, 0), before.candidates(Axis::Col, 0));
+
+            board.clear_line(Axis::Row, 1);
+
+            assert_eq!(board.grid(), before.grid());
+            for axis in [Axis::Row, Axis::Col] {
+                for idx in 0..4 {
+                    assert_eq!(board.candidates(axis, idx), before.candidates(axis, idx));
+                }
             }
         }
-    }
 
-    pub fn to_string(&self) -> String {
-        let mut s = String::new();
+        #[cfg(feature = "profiling")]
+        #[test]
+        fn test_timing_report() {
+            let (constraints, dimensions) = crate::formats::art::parse("##..#\n.##.#\n#.###\n");
+            let mut board = Board::new(constraints, dimensions);
+            board.solve();
+
+            let report = board.timing_report();
+            assert_eq!(report.len(), 3 + 5);
+            assert_eq!(report.iter().filter(|timing| timing.axis == Axis::Row).count(), 3);
+            let mut cols = report
+                .iter()
+                .filter(|timing| timing.axis == Axis::Col)
+                .map(|timing| timing.index)
+                .collect::<Vec<usize>>();
+            cols.sort();
+            assert_eq!(cols, vec![0, 1, 2, 3, 4]);
+            assert!(report.windows(2).all(|pair| pair[0].time >= pair[1].time));
+        }
+
+        #[test]
+        fn test_dump_candidates() {
+            let (constraints, dimensions) = crate::formats::art::parse("##..\n.##.\n");
+            let board = Board::new(constraints, dimensions);
+
+            // A [2] in a row of four has three arrangements
+            let rows = board.dump_candidates(Axis::Row);
+            assert_eq!(rows[0], vec!["##..", ".##.", "..##"]);
+            assert!(rows.iter().flatten().all(|line| line.len() == 4));
+            assert_eq!(board.dump_candidates(Axis::Col), vec![vec!["#.", ".#"], vec!["##"], vec!["#.", ".#"], vec![".."]]);
+            assert_eq!(Line::new(vec![CellState::Unknown, CellState::Invalid]).to_compact_string(), "?x");
+        }
+
+        #[test]
+        fn test_worst_case_line() {
+            // 6, 5 and 10 arrangements for the rows, 3 for every column
+            let rows = vec![Constraint::new(vec![1]), Constraint::new(vec![2]), Constraint::new(vec![1, 1])];
+            let cols = vec![Constraint::new(vec![1]); 6];
+            let board = Board::new(Constraints::new(cols.clone(), rows.clone()), Dimensions::new(3, 6));
+            assert_eq!(board.worst_case_line(), (2, Axis::Row, 10));
 
-        for row in self.cells.chunks(self.dimensions.num_cols) {
-            for cell in row {
-                s.push_str(&cell.to_string())
-            }
-            s.push('\n')
+            let transposed = Board::new(Constraints::new(rows.clone(), cols.clone()), Dimensions::new(6, 3));
+            assert_eq!(transposed.worst_case_line(), (2, Axis::Col, 10));
+
+            let config = SolveConfig { streaming: true, ..SolveConfig::default() };
+            let streaming = Board::with_config(Constraints::new(cols, rows), Dimensions::new(3, 6), config);
+            assert_eq!(streaming.worst_case_line(), (0, Axis::Row, 0));
         }
-        s.push('\n');
-        s
-    }
 
-    fn update_candidates(&mut self, is_row: bool) {
-        // TODO: Re-write this to re-use code for rows and cols
-        if is_row {
-            let rows = (0..self.row_candidates.len())
-                .map(|idx| self.to_line(idx, is_row))
-                .collect::<Vec<Line>>();
+        #[test]
+        fn test_hardest_line() {
+            // The middle row and column have three arrangements, the others only one. The first
+            // pass settles the outer rows, the second the columns and with them the middle row.
+            let (constraints, dimensions) = crate::formats::art::parse("#.#\n.#.\n#.#\n");
+            let mut board = Board::new(constraints.clone(), dimensions);
+            assert_eq!(board.stats().hardest_line.most_candidates, Some(board.worst_case_line()));
+            assert_eq!(board.stats().hardest_line.most_candidates, Some((1, Axis::Row, 3)));
+            assert_eq!(board.stats().hardest_line.most_passes, None);
 
-            self.row_candidates
-                .iter_mut()
-                .zip(rows.iter())
-                .for_each(|(candidates, row)| {
-                    candidates.retain(|line| {
-                        line.equivalient(row)
-                    })
-                });
-        } else {
-            let cols = (0..self.col_candidates.len())
-                .map(|idx| self.to_line(idx, is_row))
-                .collect::<Vec<Line>>();
-
-            self.col_candidates
-                .iter_mut()
-                .zip(cols.iter())
-                .for_each(|(candidates, col)| {
-                    candidates.retain(|line| {
-                        line.equivalient(col)
-                    })
-                });
+            assert_eq!(board.solve(), SolveOutcome::Solved);
+            assert_eq!(board.stats().hardest_line.most_passes, Some((1, Axis::Row, 2)));
+
+            let config = SolveConfig { streaming: true, ..SolveConfig::default() };
+            let streaming = Board::with_config(constraints, dimensions, config);
+            assert_eq!(streaming.stats().hardest_line.most_candidates, None);
         }
-    }
-        
-    fn update_cells(&mut self, is_row: bool) {
-        // TODO: Re-write this to re-use code for rows and cols
-        let length = if is_row {
-            self.dimensions.num_cols
-        } else {
-            self.dimensions.num_rows
-        };
 
-        let line_candidates = if is_row {
-            &self.row_candidates
-        } else {
-            &self.col_candidates
-        };
-         
-        let summed_lines = line_candidates
-            .iter()
-            .map(|candidates| {
-                match Line::sum(candidates) {
-                    Some(line) => line,
-                    None => Line::empty(length)
+        #[test]
+        fn test_candidate_entropy() {
+            // Every line of a full board has a single arrangement, a diagonal's have five each
+            let (constraints, dimensions) = crate::formats::art::parse("###\n###\n###\n");
+            let dense = Board::new(constraints, dimensions);
+            let (constraints, dimensions) = crate::formats::art::parse("#....\n.#...\n..#..\n...#.\n....#\n");
+            let loose = Board::new(constraints, dimensions);
+
+            assert_eq!(dense.candidate_entropy(), 0.0);
+            assert!((loose.candidate_entropy() - 10.0 * 5f64.ln() / 25.0).abs() < 1e-9);
+            assert!(dense.candidate_entropy() < loose.candidate_entropy());
+        }
+
+        #[test]
+        fn test_solve_with_patches() {
+            let apply = |patches: &[String], num_rows: usize, num_cols: usize| {
+                let mut grid = vec![vec![CellState::Unknown; num_cols]; num_rows];
+                for patch in patches {
+                    let patch = serde_json::from_str::<serde_json::Value>(patch).unwrap();
+                    for op in patch["ops"].as_array().unwrap() {
+                        let (row, col) = (op["row"].as_u64().unwrap() as usize, op["col"].as_u64().unwrap() as usize);
+                        assert!(grid[row][col].is_unknown());
+                        grid[row][col] = CellState::try_from(op["state"].as_i64().unwrap() as i8).unwrap();
+                    }
                 }
-            })
-            .collect::<Vec<Line>>();
+                grid
+            };
+
+            let (constraints, dimensions) = crate::formats::art::parse("#.##\n.##.\n##.#\n..#.\n");
+            let mut board = Board::new(constraints, dimensions);
+            let (outcome, patches) = board.solve_with_patches();
+            assert_eq!(outcome, SolveOutcome::Solved);
+            assert!(patches.len() > 1);
+            assert_eq!(apply(&patches, 4, 4), board.grid());
 
-        for (idx, line) in summed_lines.iter().enumerate() {
-            self.or_line(idx, is_row, line);
+            // Solved as soon as it's built, before any pass
+            let (constraints, dimensions) = crate::formats::art::parse("###\n###\n");
+            let mut board = Board::new(constraints, dimensions);
+            let (outcome, patches) = board.solve_with_patches();
+            assert_eq!(outcome, SolveOutcome::Solved);
+            assert_eq!(patches.len(), 1);
+            assert_eq!(apply(&patches, 2, 3), board.grid());
         }
-    }
 
-    fn num_unknown(&self) -> usize {
-        self.cells
-            .iter()
-            .filter(|&&cell| cell == CellState::Unknown)
-            .count()
-    }
+        #[test]
+        fn test_solve_with_trajectory() {
+            let dimensions = Dimensions::new(4, 4);
 
-    fn is_solved(&self) -> bool {
-        self.num_unknown() == 0
-    }
-}
+            let row_constraints = vec![
+                Constraint::new(vec![4]),
+                Constraint::new(vec![2]),
+                Constraint::new(vec![1]),
+                Constraint::new(vec![1])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![1]),
+                Constraint::new(vec![1, 1]),
+                Constraint::new(vec![2, 1]),
+                Constraint::new(vec![2])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
 
+            let mut board = Board::new(constraints, dimensions);
+            let (outcome, trajectory) = board.solve_with_trajectory();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+            assert_eq!(outcome, SolveOutcome::Solved);
+            assert_eq!(trajectory.len(), board.stats().passes);
+            assert!(trajectory.len() > 1);
+            for (before, after) in trajectory.iter().tuple_windows() {
+                assert!(after.0 <= before.0);
+                assert!(after.1 <= before.1);
+            }
+        }
 
-    mod cell_state {
-        use super::*;
+        #[test]
+        fn test_line_feasibility() {
+            let dimensions = Dimensions::new(3, 3);
+
+            let row_constraints = vec![
+                Constraint::new(vec![1]),
+                Constraint::new(vec![2, 2]),
+                Constraint::new(vec![1, 1])
+            ];
+            let col_constraints = vec![
+                Constraint::new(vec![3]),
+                Constraint::new(vec![]),
+                Constraint::new(vec![1, 1])
+            ];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let board = Board::new(constraints, dimensions);
+            let (rows, cols) = board.line_feasibility();
+
+            assert_eq!(rows, vec![true, false, true]);
+            assert_eq!(cols, vec![true, true, true]);
+        }
 
         #[test]
-        fn test_print() {
-            let full = CellState::Full;
-            let empty = CellState::Empty;
-            let unknown = CellState::Unknown;
+        fn test_is_cell_forced() {
+            let (constraints, dimensions) = crate::formats::art::parse("####.\n#...#\n.#..#\n");
 
-            println!("{} {} {}", &full, &empty, &unknown);
+            for streaming in [false, true] {
+                let config = SolveConfig { streaming, ..SolveConfig::default() };
+                let board = Board::with_config(constraints.clone(), dimensions, config);
+
+                // Any placement of the row's [4] covers the middle three cells
+                assert_eq!(board.is_cell_forced(0, 2), Some(CellState::Full));
+                // The column's [2] covers the middle cell of three
+                assert_eq!(board.is_cell_forced(1, 0), Some(CellState::Full));
+                assert_eq!(board.is_cell_forced(0, 0), None);
+                assert_eq!(board.num_unknown(), 15);
+            }
         }
 
         #[test]
-        fn test_and() {
-            let full = CellState::Full;
-            let empty = CellState::Empty;
-            let unknown = CellState::Unknown;
+        fn test_fill_forced_then_stop() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let mut board = Board::new(constraints, dimensions);
 
-            assert_eq!(full & unknown, unknown);
-            assert_eq!(empty & unknown, unknown);
-            assert_eq!(full & empty, unknown);
-            assert_eq!(full & full, full);
-            assert_eq!(empty & empty, empty);
+            // Each [2] row forces its middle cell, then [1, 1] forces the whole last row
+            assert_eq!(board.fill_forced_then_stop(), Some(Deduction { axis: Axis::Row, index: 0, cells: vec![(0, 1)] }));
+            assert_eq!(board.fill_forced_then_stop(), Some(Deduction { axis: Axis::Row, index: 1, cells: vec![(1, 1)] }));
+            assert_eq!(board.fill_forced_then_stop(), Some(Deduction { axis: Axis::Row, index: 2, cells: vec![(2, 0), (2, 1), (2, 2)] }));
+            // The rows are stuck until the first column places its [1, 1]
+            assert_eq!(board.fill_forced_then_stop(), Some(Deduction { axis: Axis::Col, index: 0, cells: vec![(0, 0), (1, 0)] }));
+            assert_eq!(board.fill_forced_then_stop(), Some(Deduction { axis: Axis::Row, index: 0, cells: vec![(0, 2)] }));
+            assert_eq!(board.fill_forced_then_stop(), Some(Deduction { axis: Axis::Row, index: 1, cells: vec![(1, 2)] }));
+            assert_eq!(board.fill_forced_then_stop(), None);
+            assert!(board.is_solved());
         }
-    
+
         #[test]
-        fn test_or() {
-            let full = CellState::Full;
-            let empty = CellState::Empty;
-            let unknown = CellState::Unknown;
-            let invalid = CellState::Invalid;
+        fn test_identical_lines_pruned_once() {
+            let art = ".######.\n".repeat(6) + "........\n";
+            let (constraints, dimensions) = crate::formats::art::parse(&art);
+            let mut board = Board::new(constraints, dimensions);
 
-            assert_eq!(full | unknown, full);
-            assert_eq!(full | full, full);
-            assert_eq!(empty | unknown, empty);
-            assert_eq!(empty | empty, empty);
-            assert_eq!(full | empty, invalid);
+            assert_eq!(board.solve(), SolveOutcome::Solved);
+            // Only the first solid row's three candidates are checked, the other five solid rows
+            // reuse its result and the empty row is already satisfied
+            assert_eq!(board.stats().passes, 1);
+            assert_eq!(board.stats().candidates_checked, 3);
         }
-    }
 
-    mod constraint {
-        use super::*;
+        #[test]
+        fn test_to_animated_svg() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let mut board = Board::new(constraints, dimensions);
+            let (outcome, frames) = board.solve_with_frames();
+            let svg = Board::to_animated_svg(&frames);
+
+            assert_eq!(outcome, SolveOutcome::Solved);
+            // The initial board plus one frame per pass
+            assert_eq!(frames.len(), board.stats().passes + 1);
+            assert_eq!(svg.matches("<g class=\"frame\"").count(), frames.len());
+            assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"30\""));
+            // The last frame shows all nine cells
+            let last_frame = svg.lines().rev().nth(1).unwrap();
+            assert_eq!(last_frame.matches("<rect").count(), 9);
+            assert_eq!(last_frame.matches("fill=\"black\"").count(), 6);
+        }
 
         #[test]
-        fn test_filter() {
-            let values = vec![1, 2];
-            let constraint = Constraint::new(values);
-            
-            let ok_line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
-            let nok_line = Line::new(vec![CellState::Full, CellState::Full, CellState::Empty, CellState::Full]);
-            let lines = vec![ok_line.clone(), nok_line.clone()];
+        fn test_overlap_only() {
+            let (constraints, dimensions) = crate::formats::art::parse("#####\n.###.\n..#..\n.##..\n##.##\n");
+            let board = Board::new(constraints, dimensions);
 
-            let filtered_lines = constraint.filter(&lines);
+            // Rows [5] and [2, 2] fill in completely and [3] fixes its middle cell. Of the
+            // columns, [2, 2] fills in, [4] fixes its middle three cells and [2, 1] its second.
+            let expected = ["#####", "?###?", "?.#??", "?##??", "##.##"]
+                .iter()
+                .map(|row| {
+                    row.chars()
+                        .map(|c| match c {
+                            '#' => CellState::Full,
+                            '.' => CellState::Empty,
+                            _ => CellState::Unknown
+                        })
+                        .collect::<Vec<CellState>>()
+                })
+                .collect::<Vec<Vec<CellState>>>();
 
-            assert_eq!(filtered_lines.len(), 1);
-            assert!(filtered_lines.contains(&ok_line));
-            assert!(!filtered_lines.contains(&nok_line))
+            assert_eq!(board.overlap_only(), expected);
+            // The board keeps only the trivial first row written when it was built
+            assert_eq!(board.num_unknown(), 20);
         }
-    }
 
-    mod line {
-        use super::*;
+        #[test]
+        fn test_is_trivially_solvable() {
+            let (overlap_only, dimensions) = crate::formats::art::parse("###\n#.#\n###\n");
+            assert!(Board::new(overlap_only, dimensions).is_trivially_solvable());
+
+            // Overlap leaves the top right cell open, only filtering the first column's
+            // candidates against the rows settles it
+            let (needs_filtering, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let mut board = Board::new(needs_filtering, dimensions);
+            assert!(!board.is_trivially_solvable());
+            assert_eq!(board.solve(), SolveOutcome::Solved);
+        }
 
         #[test]
-        fn test_to_constraint() {
-            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
-            let constraint = Constraint::new(vec![1, 2]);
+        fn test_cell_xy() {
+            // 3 columns and 2 rows, so swapping x and y would go out of bounds or hit another cell
+            let (constraints, dimensions) = crate::formats::art::parse("#..\n##.\n");
+            let mut board = Board::new(constraints, dimensions);
+            board.solve();
 
-            assert_eq!(line.to_constraint(), constraint)
+            for x in 0..3 {
+                for y in 0..2 {
+                    assert_eq!(board.cell_xy(x, y), board.cell(y, x));
+                }
+            }
+            assert_eq!(board.cell_xy(1, 0), CellState::Empty);
+            assert_eq!(board.cell(1, 0), CellState::Full);
         }
 
         #[test]
-        fn test_equivalent() {
-            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
-            let equiv_line= Line::new(vec![CellState::Full, CellState::Empty, CellState::Unknown, CellState::Unknown]);
-            let nequiv_line = Line::new(vec![CellState::Unknown, CellState::Full, CellState::Unknown, CellState::Unknown]);
+        fn test_invalid_cells() {
+            // The full first row and the empty first column are both written when the board
+            // is built and clash in the top left corner
+            let dimensions = Dimensions::new(2, 2);
+            let row_constraints = vec![Constraint::new(vec![2]), Constraint::new(vec![])];
+            let col_constraints = vec![Constraint::new(vec![]), Constraint::new(vec![1])];
+            let mut board = Board::new(Constraints::new(col_constraints, row_constraints), dimensions);
 
-            assert!(line.equivalient(&equiv_line));
-            assert!(!line.equivalient(&nequiv_line));
+            assert_eq!(board.solve(), SolveOutcome::Contradiction { index: 0, axis: Axis::Row });
+            assert_eq!(board.stats().invalid_cells, vec![(0, 0)]);
+            assert_eq!(board.invalid_cells(), vec![(0, 0)]);
+
+            // Every cell is determined, but the middle one both full and empty
+            let row_constraints = vec![Constraint::new(vec![3])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![]), Constraint::new(vec![1])];
+            let mut board = Board::new(Constraints::new(col_constraints, row_constraints), Dimensions::new(1, 3));
+
+            assert_eq!(board.solve(), SolveOutcome::Contradiction { index: 0, axis: Axis::Row });
+            assert_eq!(board.invalid_cells(), vec![(0, 1)]);
         }
 
         #[test]
-        fn test_filter() {
-            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
-            let equiv_line= Line::new(vec![CellState::Full, CellState::Empty, CellState::Unknown, CellState::Unknown]);
-            let nequiv_line = Line::new(vec![CellState::Unknown, CellState::Full, CellState::Unknown, CellState::Unknown]);            
-            let line_candidates = vec![line.clone(), equiv_line.clone(), nequiv_line.clone()];
+        fn test_is_consistent_with() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let board = Board::new(constraints, dimensions);
+            let (full, empty, unknown) = (CellState::Full, CellState::Empty, CellState::Unknown);
 
-            let filtered_lines = line.filter(&line_candidates);
+            let correct = vec![vec![full, unknown, empty], vec![unknown; 3], vec![unknown; 3]];
+            let wrong_fill = vec![vec![unknown, unknown, full], vec![unknown; 3], vec![unknown; 3]];
+            let wrong_empty = vec![vec![unknown; 3], vec![unknown; 3], vec![unknown, unknown, empty]];
 
-            assert_eq!(filtered_lines.len(), 2);
-            assert!(filtered_lines.contains(&line));
-            assert!(filtered_lines.contains(&equiv_line));
-            assert!(!filtered_lines.contains(&nequiv_line));
+            assert!(board.is_consistent_with(&correct));
+            assert!(!board.is_consistent_with(&wrong_fill));
+            assert!(!board.is_consistent_with(&wrong_empty));
+            assert!(!board.is_consistent_with(&[vec![unknown; 3]]));
         }
 
         #[test]
-        fn test_print() {
-            let line = Line::new(vec![CellState::Full, CellState::Empty, CellState::Full, CellState::Full]);
-            println!("{}", &line)
+        fn test_satisfied_line_skipped() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let mut board = Board::new(constraints, dimensions);
+            let num_row_candidates = board.candidates(Axis::Row, 0).len();
+            for (col, state) in [CellState::Full, CellState::Full, CellState::Empty].into_iter().enumerate() {
+                board.set_cell(0, col, state);
+            }
+
+            // The other rows have nothing known yet, so no candidate is checked at all
+            assert!(board.merge_axis_once(Axis::Row).is_ok());
+            assert!(num_row_candidates > 1);
+            assert_eq!(board.candidates(Axis::Row, 0).len(), 1);
+            assert_eq!(board.stats().candidates_checked, 0);
+
+            assert_eq!(board.solve(), SolveOutcome::Solved);
         }
 
         #[test]
-        fn test_and() {
-            let a = Line::new(vec![CellState::Empty, CellState::Empty, CellState::Empty, CellState::Full, CellState::Full, CellState::Full]);
-            let b = Line::new(vec![CellState::Empty, CellState::Full, CellState::Unknown, CellState::Empty, CellState::Full, CellState::Unknown]);
-            let a_and_b = Line::new(vec![CellState::Empty, CellState::Unknown, CellState::Unknown, CellState::Unknown, CellState::Full, CellState::Unknown]);
+        fn test_solve_partial() {
+            let (constraints, dimensions) = crate::formats::art::parse("##.\n.##\n#.#\n");
+            let mut board = Board::new(constraints, dimensions);
 
-            let res = &a & &b;
-            assert_eq!(res, a_and_b)    
+            assert_eq!(board.solve_partial(3), 3);
+            assert_eq!(board.num_unknown(), 6);
+            // The first row is revealed
+            assert_eq!(board.grid()[0], vec![CellState::Full, CellState::Full, CellState::Empty]);
+
+            assert_eq!(board.solve_partial(100), 6);
+            assert!(board.is_solved());
+            assert_eq!(board.solve_partial(1), 0);
         }
-        
+
         #[test]
-        fn test_or() {
-            let a = Line::new(vec![CellState::Empty, CellState::Empty, CellState::Empty, CellState::Full, CellState::Full, CellState::Full]);
-            let b = Line::new(vec![CellState::Empty, CellState::Full, CellState::Unknown, CellState::Empty, CellState::Full, CellState::Unknown]);
-            let a_or_b = Line::new(vec![CellState::Empty, CellState::Invalid, CellState::Empty, CellState::Invalid, CellState::Full, CellState::Full]);
+        fn test_adaptive_direction() {
+            let (constraints, dimensions) = crate::formats::art::parse("##..#\n.##.#\n#.#..\n.####\n#...#\n");
+            let config = SolveConfig { adaptive_direction: true, ..SolveConfig::default() };
+            let mut fixed = Board::new(constraints.clone(), dimensions);
+            let mut adaptive = Board::with_config(constraints, dimensions, config);
 
-            let res = &a | &b;
-            assert_eq!(res, a_or_b)    
+            assert_eq!(fixed.solve(), SolveOutcome::Solved);
+            assert_eq!(adaptive.solve(), SolveOutcome::Solved);
+            assert_eq!(fixed.grid(), adaptive.grid());
+            // A pass only writes what its own lines' candidates agree on, so repeating it right
+            // away never finds anything new and every switch costs an extra pass
+            assert!(adaptive.stats().passes > fixed.stats().passes);
         }
 
         #[test]
-        fn test_sum() {
-            let a = Line::new(vec![CellState::Empty, CellState::Empty, CellState::Empty, CellState::Full, CellState::Full, CellState::Full]);
-            let b = Line::new(vec![CellState::Empty, CellState::Full, CellState::Unknown, CellState::Empty, CellState::Full, CellState::Unknown]);
-            let a_and_b = Line::new(vec![CellState::Empty, CellState::Unknown, CellState::Unknown, CellState::Unknown, CellState::Full, CellState::Unknown]);
+        fn test_line_string() {
+            // 3 wide and 5 tall
+            let (constraints, dimensions) = crate::formats::art::parse("#..\n##.\n###\n.#.\n..#\n");
+            let mut board = Board::new(constraints, dimensions);
+            board.set_cell(1, 0, CellState::Full);
+            board.set_cell(4, 0, CellState::Empty);
 
-            let res = Line::sum(&vec![a, b]).unwrap();
-            assert_eq!(res, a_and_b)    
+            // The full third row is written when the board is built
+            assert_eq!(board.line_string(Axis::Col, 0), "-██-░");
+            assert_eq!(board.line_string(Axis::Col, 2).chars().count(), 5);
+            assert_eq!(board.line_string(Axis::Row, 1), "█--");
         }
-    }
 
-    mod Board {
-        use crate::model::Board;
+        #[test]
+        fn test_generate() {
+            let board = Board::generate(6, 8, 0.5, 42);
+            assert_eq!(board.grid().len(), 6);
+            assert!(board.grid().iter().all(|row| row.len() == 8));
+            assert!(board.is_solved());
+            assert_eq!(board.validate(&board.grid()), Ok(()));
 
-        use super::*;
+            assert_eq!(Board::generate(6, 8, 0.5, 42).grid(), board.grid());
+            assert_ne!(Board::generate(6, 8, 0.5, 43).grid(), board.grid());
+            assert!(Board::generate(3, 3, 0.0, 42).grid().iter().flatten().all(|&cell| cell == CellState::Empty));
+            assert!(Board::generate(3, 3, 1.0, 42).grid().iter().flatten().all(|&cell| cell == CellState::Full));
+        }
 
         #[test]
-        fn test_is_solved() {
-            let dimensions = Dimensions::new(2, 4);
-            
+        fn test_from_solution_with_noise() {
+            let grid = |art: &str| {
+                art.lines()
+                    .map(|row| row.chars().map(|c| if c == '#' { CellState::Full } else { CellState::Empty }).collect())
+                    .collect::<Vec<Vec<CellState>>>()
+            };
+
+            let mut tough = Board::from_solution_with_noise(&grid("#..#\n.#.#\n.#..\n#..#")).unwrap();
+            assert_eq!(tough.clone().solve(), SolveOutcome::Stalled);
+            assert_eq!(tough.solve_any(), Some(grid("#..#\n.#.#\n.#..\n#..#")));
+
+            // Solved by propagation alone
+            assert!(Board::from_solution_with_noise(&grid("##.\n.##\n#.#")).is_none());
+            // Stalls, but the mirrored diagonal fits the clues as well
+            assert!(Board::from_solution_with_noise(&grid("#.\n.#")).is_none());
+        }
+
+        #[test]
+        fn test_solve_window() {
+            let (constraints, dimensions) = crate::formats::art::parse("##..#\n.##.#\n#.#..\n.####\n#...#\n");
+            let mut full = Board::new(constraints.clone(), dimensions);
+            let mut windowed = Board::new(constraints, dimensions);
+
+            assert_eq!(full.solve(), SolveOutcome::Solved);
+            let window = windowed.solve_window(1..3, 0..2);
+
+            let expected = full.grid()[1..3]
+                .iter()
+                .map(|row| row[0..2].to_vec())
+                .collect::<Vec<Vec<CellState>>>();
+            assert_eq!(window, expected);
+        }
+
+        #[test]
+        fn test_reveal_line() {
+            let dimensions = Dimensions::new(3, 4);
+
             let row_constraints = vec![
-                Constraint::new(vec![1, 2]),
-                Constraint::new(vec![1, 2])
+                Constraint::new(vec![2, 1]),
+                Constraint::new(vec![1]),
+                Constraint::new(vec![1, 1])
             ];
             let col_constraints = vec![
+                Constraint::new(vec![1, 1]),
                 Constraint::new(vec![2]),
                 Constraint::new(vec![]),
-                Constraint::new(vec![2]),
-                Constraint::new(vec![2])
+                Constraint::new(vec![1, 1])
             ];
             let constraints = Constraints::new(col_constraints, row_constraints);
-            
+
+            let mut board = Board::new(constraints, dimensions);
+
+            // A failed reveal doesn't prune the line's candidates against the cell set by hand
+            board.set_cell(1, 3, CellState::Empty);
+            assert_eq!(board.reveal_line(Axis::Row, 1), Err(SolverError::LineNotForced { index: 1, axis: Axis::Row }));
+            assert_eq!(board.to_line(1, Axis::Row).cells()[..2], [CellState::Unknown, CellState::Unknown]);
+            assert_eq!(board.candidates(Axis::Row, 1).len(), 4);
+
+            assert_eq!(board.reveal_line(Axis::Row, 0), Ok(()));
+            assert_eq!(board.to_line(0, Axis::Row), Line::new(vec![CellState::Full, CellState::Full, CellState::Empty, CellState::Full]));
+            assert_eq!(board.col_candidates[1].len(), 1);
+        }
+
+        #[test]
+        fn test_streaming_matches_stored_candidates() {
+            let art = "
+##.#.#####
+#.##...#..
+.####.#..#
+#..#.####.
+.##...#.##
+###.#.....
+..#####.#.
+#.#..###.#
+####.#.##.
+.#..###.##
+";
+            let (constraints, _) = crate::formats::art::parse(art);
+
+            let streaming_config = SolveConfig {
+                streaming: true,
+                ..SolveConfig::default()
+            };
+            let mut stored = Board::new(constraints.clone(), Dimensions::new(10, 10));
+            let mut streamed = Board::with_config(constraints, Dimensions::new(10, 10), streaming_config);
+
+            stored.solve();
+            streamed.solve();
+
+            assert_eq!(streamed.to_string(), stored.to_string());
+            assert_eq!(streamed.stats().candidates_generated, 0);
+        }
+
+        #[test]
+        fn test_apply_solution() {
+            let (constraints, dimensions) = crate::formats::art::parse("#.#\n.##\n");
+            let mut board = Board::new(constraints, dimensions);
+
+            let full = CellState::Full;
+            let empty = CellState::Empty;
+            let solution = vec![vec![full, empty, full], vec![empty, full, full]];
+            let wrong_row = vec![vec![full, empty, full], vec![full, empty, full]];
+            let wrong_col = vec![vec![full, empty, full], vec![full, full, empty]];
+
+            assert_eq!(board.apply_solution(&wrong_row), Err(SolverError::LineMismatch { index: 1, axis: Axis::Row }));
+            assert_eq!(board.apply_solution(&wrong_col), Err(SolverError::LineMismatch { index: 0, axis: Axis::Col }));
+            assert_eq!(
+                board.apply_solution(&solution[..1]),
+                Err(SolverError::GridSizeMismatch { expected: (2, 3), actual: (1, 3) })
+            );
+            assert!(!board.is_solved());
+
+            assert_eq!(board.apply_solution(&solution), Ok(()));
+            assert!(board.is_solved());
+            assert_eq!(board.grid(), solution);
+        }
+
+        #[test]
+        fn test_bounding_box() {
+            let (constraints, dimensions) = crate::formats::art::parse(".....\n.....\n..##.\n..#..\n.....\n");
+            let mut board = Board::new(constraints, dimensions);
+            board.solve();
+
+            assert!(board.is_solved());
+            assert_eq!(board.bounding_box(), Some((2, 3, 2, 3)));
+
+            let (constraints, dimensions) = crate::formats::art::parse("...\n...\n");
             let board = Board::new(constraints, dimensions);
+            assert_eq!(board.bounding_box(), None);
+        }
 
-            // TODO: Implement this
+        #[test]
+        fn test_labeled_string_alignment() {
+            let dimensions = Dimensions::new(10, 10);
+
+            let row_constraints = (0..10)
+                .map(|_| Constraint::new(vec![1]))
+                .collect::<Vec<Constraint>>();
+            let mut col_constraints = vec![Constraint::new(vec![10])];
+            col_constraints.extend((1..10).map(|_| Constraint::new(vec![])));
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let board = Board::new(constraints, dimensions);
+            let labeled = board.to_labeled_string();
+            let lines = labeled.lines().collect::<Vec<&str>>();
+
+            // Two-digit clues widen every column to three glyphs, the row labels are one wide
+            assert_eq!(lines.len(), 11);
+            assert_eq!(lines[0], format!("{}{}", "  10", " ".repeat(27)));
+            for line in &lines[1..] {
+                assert_eq!(*line, format!("1███{}", "░".repeat(27)));
+            }
+            assert!(lines.iter().all(|line| line.width() == lines[0].width()));
+        }
+
+        #[test]
+        fn test_labeled_string_single_line() {
+            let (constraints, dimensions) = crate::formats::art::parse("#.##.");
+            let mut single_row = Board::new(constraints, dimensions);
+            single_row.solve();
+            // The row label takes the whole left margin and every column clue sits above its cell
+            assert_eq!(single_row.to_labeled_string(), "    1   1 1  \n1 2██░░████░░\n");
+
+            let (constraints, dimensions) = crate::formats::art::parse("#\n.\n#\n#\n.");
+            let mut single_col = Board::new(constraints, dimensions);
+            single_col.solve();
+            // The column clue is stacked above the only column
+            assert_eq!(single_col.to_labeled_string(), "  1\n  2\n1██\n ░░\n1██\n1██\n ░░\n");
+        }
+
+        #[test]
+        fn test_ascii_string_cell_width() {
+            let dimensions = Dimensions::new(2, 3);
+            let row_constraints = vec![Constraint::new(vec![3]), Constraint::new(vec![1])];
+            let col_constraints = vec![Constraint::new(vec![1]), Constraint::new(vec![1]), Constraint::new(vec![1])];
+            let constraints = Constraints::new(col_constraints, row_constraints);
+
+            let board = Board::new(constraints, dimensions);
+            let ascii = board.to_ascii_string();
+            let lines = ascii.lines().collect::<Vec<&str>>();
+
+            assert_eq!(lines.len(), 2);
+            assert_eq!(lines[0], "[][][]");
+            for line in &lines {
+                assert_eq!(line.chars().count(), 2 * dimensions.num_cols());
+            }
+            for cell in [CellState::Full, CellState::Empty, CellState::Unknown, CellState::Invalid] {
+                assert_eq!(cell.to_ascii().chars().count(), 2);
+                assert!(cell.to_ascii().is_ascii());
+            }
+        }
+    }
+
+    mod solver_handle {
+        use super::*;
+
+        #[test]
+        fn test_status_while_running() {
+            let (constraints, dimensions) = crate::formats::art::parse("#####\n.###.\n..#..\n.##..\n##.##\n");
+            let mut solved = Board::new(constraints.clone(), dimensions);
+            assert_eq!(solved.solve(), SolveOutcome::Solved);
+            assert!(solved.stats().passes > 1);
+
+            let mut handle = SolverHandle::new(Board::new(constraints, dimensions));
+            assert_eq!(handle.step(1), HandleState::Running);
+
+            let status = handle.status();
+            assert_eq!(status.state, HandleState::Running);
+            assert_eq!(status.grid, handle.board().grid());
+            assert_eq!(status.determined, 25 - handle.board().num_unknown());
+            assert!(status.determined > 5 && status.determined < 25);
+            assert_eq!(handle.outcome(), None);
+
+            assert_eq!(handle.step(usize::MAX), HandleState::Solved);
+            assert_eq!(handle.status().grid, solved.grid());
+            assert_eq!(handle.board().stats().passes, solved.stats().passes);
+        }
+
+        #[test]
+        fn test_status_when_stalled() {
+            // Unique, but propagation alone can't settle it
+            let (constraints, dimensions) = crate::formats::art::parse("#..#\n.#.#\n.#..\n#..#\n");
+            let mut handle = SolverHandle::new(Board::new(constraints, dimensions));
+
+            assert_eq!(handle.step(usize::MAX), HandleState::Stalled);
+            assert_eq!(handle.outcome(), Some(SolveOutcome::Stalled));
+            assert!(handle.status().determined < 16);
         }
     }
-}
\ No newline at end of file
+}